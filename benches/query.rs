@@ -0,0 +1,99 @@
+//! A benchmark comparing evaluating the queries of all built-in lints
+//! as a single combined query against evaluating each lint's query
+//! separately, on a scaled up version of
+//! `examples/task_longrun.bpf.c`.
+//!
+//! The code is parsed only once and all queries run on the same syntax
+//! tree. Custom predicates are not evaluated, as their cost depends on
+//! the number of matches only, which is the same either way.
+//!
+//! Run via:
+//! ```sh
+//! $ cargo bench --bench query
+//! ```
+
+use std::hint::black_box;
+use std::time::Duration;
+use std::time::Instant;
+
+use tree_sitter::Parser;
+use tree_sitter::Query;
+use tree_sitter::QueryCursor;
+use tree_sitter::StreamingIterator as _;
+use tree_sitter::Tree;
+use tree_sitter_bpf_c::LANGUAGE;
+
+use bpflint::builtin_lints;
+
+
+/// The number of times each measurement is repeated.
+const ITERATIONS: u32 = 10;
+/// The number of copies of the example program to lint.
+const SCALE: usize = 200;
+
+
+/// Run `f` repeatedly, returning the minimum time taken by any
+/// iteration along with its result.
+fn bench<F, T>(mut f: F) -> (Duration, T)
+where
+    F: FnMut() -> T,
+{
+    let mut min = Duration::MAX;
+    let mut result = None;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let r = black_box(f());
+        min = min.min(start.elapsed());
+        result = Some(r);
+    }
+    // SANITY: `ITERATIONS` is non-zero.
+    (min, result.unwrap())
+}
+
+/// Count the matches of `query` on `tree`.
+fn count_matches(query: &Query, tree: &Tree, code: &[u8]) -> usize {
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), code);
+    let mut count = 0;
+    while matches.next().is_some() {
+        count += 1;
+    }
+    count
+}
+
+
+fn main() {
+    let code = include_str!("../examples/task_longrun.bpf.c").repeat(SCALE);
+    let code = code.as_bytes();
+
+    let mut parser = Parser::new();
+    let () = parser.set_language(&LANGUAGE.into()).unwrap();
+    let (parse, tree) = bench(|| parser.parse(code, None).unwrap());
+
+    let lints = builtin_lints().collect::<Vec<_>>();
+    let source = lints
+        .iter()
+        .map(|lint| format!("{}\n", lint.code))
+        .collect::<String>();
+    let combined = Query::new(&LANGUAGE.into(), &source).unwrap();
+    let (combined, combined_count) = bench(|| count_matches(&combined, &tree, code));
+
+    println!(
+        "{} bytes, {combined_count} query matches (best of {ITERATIONS} runs)",
+        code.len()
+    );
+    println!("  parsing:          {parse:?}");
+    println!("  combined query:   {combined:?}");
+
+    let mut total = Duration::ZERO;
+    let mut total_count = 0;
+    for lint in &lints {
+        let query = Query::new(&LANGUAGE.into(), &lint.code).unwrap();
+        let (duration, count) = bench(|| count_matches(&query, &tree, code));
+        println!("    {}: {duration:?}", lint.name);
+        total += duration;
+        total_count += count;
+    }
+    println!("  separate queries: {total:?}");
+    assert_eq!(combined_count, total_count);
+}
@@ -1,6 +1,7 @@
 use std::fs::File;
 use std::io::BufRead as _;
 use std::io::BufReader;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 use anyhow::Context as _;
@@ -8,7 +9,9 @@ use anyhow::Result;
 
 use clap::ArgAction;
 use clap::Parser;
+use clap::ValueEnum;
 
+use bpflint::Level;
 use bpflint::Version;
 
 
@@ -57,16 +60,68 @@ fn parse_context_line_count(s: &str) -> Result<u8> {
     Ok(line_count)
 }
 
+/// The format in which to report lint matches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable text, including source code snippets.
+    #[default]
+    Text,
+    /// A machine readable JSON document.
+    Json,
+    /// A SARIF 2.1.0 log, for consumption by code scanning services.
+    Sarif,
+}
+
+/// The level at which to report additional findings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportLevel {
+    /// Report findings as warnings.
+    Warn,
+    /// Report findings as errors, causing the program to exit with
+    /// failure.
+    Deny,
+}
+
+impl From<ReportLevel> for Level {
+    fn from(other: ReportLevel) -> Self {
+        match other {
+            ReportLevel::Warn => Level::Warn,
+            ReportLevel::Deny => Level::Deny,
+        }
+    }
+}
+
 /// A command line interface for bpflint.
 #[derive(Debug, Parser)]
 #[clap(version = env!("VERSION"))]
 pub struct Args {
-    /// The BPF C source files to lint.
+    /// The BPF C source files or directories to lint.
     ///
-    /// Use '@file' syntax to include a (newline separated) list of
-    /// files from 'file'.
-    #[clap(required = true, value_name = "[@]SRCS", value_parser = parse_files)]
+    /// Directories are searched recursively for '*.bpf.c' files,
+    /// honoring '.gitignore' files. Use '@file' syntax to include a
+    /// (newline separated) list of files from 'file'. Use '-' to read
+    /// source code from standard input.
+    #[clap(
+        required_unless_present = "print_lints",
+        value_name = "[@]SRCS",
+        value_parser = parse_files
+    )]
     pub srcs: Vec<Vec<PathBuf>>,
+    /// The file name to use for source code read from standard input,
+    /// e.g., in reports and for configuration file discovery.
+    #[clap(long = "stdin-filename", value_name = "PATH")]
+    pub stdin_filename: Option<PathBuf>,
+    /// A glob matching additional files to lint when searching
+    /// directories, e.g., '*.bpf.h' (can be supplied multiple times).
+    ///
+    /// Globs are matched against paths relative to the directory
+    /// being searched.
+    #[clap(long = "include", value_name = "GLOB")]
+    pub includes: Vec<String>,
+    /// A glob matching files or directories to skip when searching
+    /// directories (can be supplied multiple times).
+    #[clap(long = "exclude", value_name = "GLOB")]
+    pub excludes: Vec<String>,
     /// Number of lines to show before the lint match.
     #[clap(short = 'B', long = "before", value_parser = parse_context_line_count)]
     pub before: Option<u8>,
@@ -76,11 +131,17 @@ pub struct Args {
     /// Number of lines to show before and after the lint match.
     #[clap(short = 'C', long = "context", value_parser = parse_context_line_count, conflicts_with_all = ["before", "after"])]
     pub context: Option<u8>,
+    /// The format in which to report lint matches.
+    #[clap(long = "format", value_enum, default_value_t = Format::Text)]
+    pub format: Format,
     /// Whether or not to color output and or syntax highlighting for code snippets.
     #[clap(long = "color")]
     pub color: Option<bool>,
     /// Print a list of available lints.
-    #[clap(long, exclusive = true)]
+    ///
+    /// Lints loaded via '--lint-dir' as well as those configured for
+    /// the current directory are included.
+    #[clap(long, conflicts_with = "srcs")]
     pub print_lints: bool,
     /// Increase verbosity (can be supplied multiple times).
     #[clap(short = 'v', long = "verbose", global = true, action = ArgAction::Count)]
@@ -88,6 +149,98 @@ pub struct Args {
     /// User kernel version to check lints against
     #[arg(short = 'k', long = "kernel_version")]
     pub kernel_version: Option<Version>,
+    /// A directory containing additional lints to use (can be supplied
+    /// multiple times).
+    ///
+    /// Each lint consists of a '<name>.scm' file containing the
+    /// tree-sitter query and a '<name>.txt' file containing the
+    /// message to report.
+    #[arg(long = "lint-dir", value_name = "DIR")]
+    pub lint_dirs: Vec<PathBuf>,
+    /// Do not report matches of the given lint (can be supplied
+    /// multiple times; use 'all' to refer to all lints).
+    #[arg(long = "allow", value_name = "LINT")]
+    pub allow: Vec<String>,
+    /// Report matches of the given lint as warnings (can be supplied
+    /// multiple times; use 'all' to refer to all lints).
+    #[arg(long = "warn", value_name = "LINT")]
+    pub warn: Vec<String>,
+    /// Report matches of the given lint as errors, causing the program
+    /// to exit with failure (can be supplied multiple times; use 'all'
+    /// to refer to all lints).
+    ///
+    /// Levels are applied in the order allow, warn, deny and take
+    /// precedence over any configured ones.
+    #[arg(long = "deny", value_name = "LINT")]
+    pub deny: Vec<String>,
+    /// Report code that failed to parse as 'parse-error' matches, at
+    /// the given level.
+    ///
+    /// Lints may not detect issues in code that failed to parse, which
+    /// by default goes unreported.
+    #[arg(long = "parse-errors", value_name = "LEVEL", value_enum)]
+    pub parse_errors: Option<ReportLevel>,
+    /// Report 'bpflint:' directives that did not suppress any lint
+    /// match, that refer to unknown lints, or that are malformed, at
+    /// the given level.
+    #[arg(long = "unused-directives", value_name = "LEVEL", value_enum)]
+    pub unused_directives: Option<ReportLevel>,
+    /// Report 'bpflint:' directives disabling lints without providing a
+    /// reason (as in 'bpflint: disable=<lint> -- <reason>'), at the
+    /// given level.
+    #[arg(long = "unjustified-directives", value_name = "LEVEL", value_enum)]
+    pub unjustified_directives: Option<ReportLevel>,
+    /// Apply suggested fixes to the source files in place and report
+    /// the remaining lint matches.
+    #[arg(long = "fix")]
+    pub fix: bool,
+    /// Print a unified diff of the fixes that '--fix' would apply,
+    /// without modifying any files.
+    ///
+    /// The program exits with failure if any fixes are available.
+    #[arg(long = "fix-dry-run", conflicts_with_all = ["fix", "format"])]
+    pub fix_dry_run: bool,
+    /// A baseline file listing accepted lint matches, which will not
+    /// be reported.
+    ///
+    /// Baseline entries no longer matching any code in the linted
+    /// files are reported on standard error, so that the baseline can
+    /// be updated.
+    ///
+    /// Fixing is not supported, as it would rewrite code the baseline
+    /// accepts.
+    #[arg(
+        long = "baseline",
+        value_name = "FILE",
+        conflicts_with_all = ["fix", "fix_dry_run"]
+    )]
+    pub baseline: Option<PathBuf>,
+    /// Record all lint matches in the given baseline file instead of
+    /// reporting them.
+    ///
+    /// Matches are identified by lint name, path, and the affected
+    /// source code lines, not by line numbers, so that the baseline
+    /// stays valid as unrelated code changes.
+    #[arg(
+        long = "write-baseline",
+        value_name = "FILE",
+        conflicts_with_all = ["baseline", "fix", "fix_dry_run"]
+    )]
+    pub write_baseline: Option<PathBuf>,
+    /// Only report lint matches on lines added or modified by the
+    /// given unified diff (e.g., as produced by 'git diff'); use '-' to
+    /// read it from standard input.
+    ///
+    /// Files not covered by the diff are skipped. Paths in the diff are
+    /// interpreted relative to the current working directory.
+    #[arg(long = "diff", value_name = "FILE", conflicts_with_all = ["fix", "fix_dry_run"])]
+    pub diff: Option<PathBuf>,
+    /// The number of files to lint in parallel.
+    ///
+    /// Matches are reported in the order in which files were provided,
+    /// irrespective of this setting.
+    #[arg(short = 'j', long = "jobs", value_name = "N", default_value = "1")]
+    pub jobs: NonZeroUsize,
 }
 
 
@@ -152,6 +305,19 @@ mod tests {
         );
     }
 
+    /// Check that we can request reading of source code from standard
+    /// input.
+    #[test]
+    fn stdin_parsing() {
+        let args = try_parse(["-"]).unwrap();
+        assert_eq!(args.srcs, vec![vec![PathBuf::from("-")]]);
+        assert_eq!(args.stdin_filename, None);
+
+        let args = try_parse(["--stdin-filename", "src/foo.bpf.c", "-"]).unwrap();
+        assert_eq!(args.srcs, vec![vec![PathBuf::from("-")]]);
+        assert_eq!(args.stdin_filename, Some(PathBuf::from("src/foo.bpf.c")));
+    }
+
     /// Test that -C cannot be combined with -A or -B using clap groups.
     #[test]
     fn context_conflict_validation() {
@@ -168,6 +334,150 @@ mod tests {
         assert!(try_parse(["test.c", "-B", "2", "-A", "4"]).is_ok());
     }
 
+    /// Check that the output format can be selected.
+    #[test]
+    fn format_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.format, Format::Text);
+
+        let args = try_parse(["test.c", "--format", "json"]).unwrap();
+        assert_eq!(args.format, Format::Json);
+
+        let args = try_parse(["test.c", "--format", "sarif"]).unwrap();
+        assert_eq!(args.format, Format::Sarif);
+
+        assert!(try_parse(["test.c", "--format", "xml"]).is_err());
+    }
+
+    /// Check that lint levels can be provided multiple times.
+    #[test]
+    fn lint_level_parsing() {
+        let args = try_parse([
+            "test.c",
+            "--deny",
+            "all",
+            "--allow",
+            "probe-read",
+            "--allow",
+            "core-read",
+        ])
+        .unwrap();
+        assert_eq!(args.allow, vec!["probe-read", "core-read"]);
+        assert_eq!(args.warn, Vec::<String>::new());
+        assert_eq!(args.deny, vec!["all"]);
+    }
+
+    /// Check that fix related arguments are parsed correctly.
+    #[test]
+    fn fix_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert!(!args.fix);
+        assert!(!args.fix_dry_run);
+
+        let args = try_parse(["test.c", "--fix"]).unwrap();
+        assert!(args.fix);
+
+        let args = try_parse(["test.c", "--fix-dry-run"]).unwrap();
+        assert!(args.fix_dry_run);
+
+        assert!(try_parse(["test.c", "--fix", "--fix-dry-run"]).is_err());
+        assert!(try_parse(["test.c", "--fix-dry-run", "--format", "json"]).is_err());
+    }
+
+    /// Check that the `--parse-errors` argument is parsed correctly.
+    #[test]
+    fn parse_errors_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.parse_errors, None);
+
+        let args = try_parse(["test.c", "--parse-errors", "warn"]).unwrap();
+        assert_eq!(args.parse_errors, Some(ReportLevel::Warn));
+
+        let args = try_parse(["test.c", "--parse-errors=deny"]).unwrap();
+        assert_eq!(args.parse_errors, Some(ReportLevel::Deny));
+
+        assert!(try_parse(["test.c", "--parse-errors", "allow"]).is_err());
+    }
+
+    /// Check that the `--unused-directives` and
+    /// `--unjustified-directives` arguments are parsed correctly.
+    #[test]
+    fn unused_directives_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.unused_directives, None);
+
+        let args = try_parse(["test.c", "--unused-directives", "warn"]).unwrap();
+        assert_eq!(args.unused_directives, Some(ReportLevel::Warn));
+
+        let args = try_parse(["test.c", "--unused-directives=deny"]).unwrap();
+        assert_eq!(args.unused_directives, Some(ReportLevel::Deny));
+
+        assert!(try_parse(["test.c", "--unused-directives", "allow"]).is_err());
+
+        let args = try_parse(["test.c", "--unjustified-directives", "deny"]).unwrap();
+        assert_eq!(args.unjustified_directives, Some(ReportLevel::Deny));
+    }
+
+    /// Check that baseline related arguments are parsed correctly.
+    #[test]
+    fn baseline_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.baseline, None);
+        assert_eq!(args.write_baseline, None);
+
+        let args = try_parse(["test.c", "--baseline", "baseline.json"]).unwrap();
+        assert_eq!(args.baseline, Some(PathBuf::from("baseline.json")));
+
+        let args = try_parse(["test.c", "--write-baseline", "baseline.json"]).unwrap();
+        assert_eq!(args.write_baseline, Some(PathBuf::from("baseline.json")));
+
+        assert!(try_parse(["test.c", "--baseline", "a", "--write-baseline", "b"]).is_err());
+        assert!(try_parse(["test.c", "--write-baseline", "a", "--fix"]).is_err());
+        assert!(try_parse(["test.c", "--baseline", "a", "--fix"]).is_err());
+        assert!(try_parse(["test.c", "--baseline", "a", "--fix-dry-run"]).is_err());
+    }
+
+    /// Check that the `--diff` argument is parsed correctly.
+    #[test]
+    fn diff_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.diff, None);
+
+        let args = try_parse(["test.c", "--diff", "-"]).unwrap();
+        assert_eq!(args.diff, Some(PathBuf::from("-")));
+
+        assert!(try_parse(["test.c", "--diff", "changes.diff", "--fix"]).is_err());
+    }
+
+    /// Check that the `--jobs` argument is parsed correctly.
+    #[test]
+    fn jobs_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.jobs.get(), 1);
+
+        let args = try_parse(["test.c", "-j", "8"]).unwrap();
+        assert_eq!(args.jobs.get(), 8);
+
+        let args = try_parse(["test.c", "--jobs=2"]).unwrap();
+        assert_eq!(args.jobs.get(), 2);
+
+        assert!(try_parse(["test.c", "-j", "0"]).is_err());
+    }
+
+    /// Check that the `--print-lints` argument is parsed correctly.
+    #[test]
+    fn print_lints_parsing() {
+        let args = try_parse(["--print-lints"]).unwrap();
+        assert!(args.print_lints);
+        assert!(args.srcs.is_empty());
+
+        let args = try_parse(["--print-lints", "--lint-dir", "lints"]).unwrap();
+        assert_eq!(args.lint_dirs, vec![PathBuf::from("lints")]);
+
+        assert!(try_parse(["--print-lints", "test.c"]).is_err());
+        assert!(try_parse(Vec::<OsString>::new()).is_err());
+    }
+
     /// Test `parse_context_line_count` function directly.
     #[test]
     fn parse_context_line_count_validation() {
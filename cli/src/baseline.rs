@@ -0,0 +1,356 @@
+//! Support for baseline files recording accepted lint matches.
+//!
+//! Matches are identified by a fingerprint consisting of the lint name,
+//! the path of the file (relative to the baseline file), and the
+//! normalized source code lines containing the match. Line numbers are
+//! deliberately not part of it, so that unrelated edits shifting code
+//! around do not invalidate the baseline.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::read_to_string;
+use std::io::BufWriter;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::path::absolute;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::ensure;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use bpflint::LintMatch;
+use bpflint::Range;
+
+
+/// The version of the baseline file format.
+const VERSION: u32 = 1;
+
+
+/// The fingerprint identifying a lint match.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct Fingerprint {
+    /// The name of the lint that matched.
+    pub lint: String,
+    /// The path to the file containing the match, relative to the
+    /// baseline file.
+    pub path: String,
+    /// The normalized source code lines containing the match.
+    pub snippet: String,
+}
+
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RawEntry {
+    #[serde(flatten)]
+    fingerprint: Fingerprint,
+    count: usize,
+}
+
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RawBaseline {
+    version: u32,
+    entries: Vec<RawEntry>,
+}
+
+
+/// Normalize the source code lines of `code` covered by `range`, by
+/// collapsing all white space.
+fn normalize_snippet(code: &[u8], range: &Range) -> String {
+    let start = range.bytes.start.min(code.len());
+    let end = range.bytes.end.clamp(start, code.len());
+    let start = code[..start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let end = code[end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|idx| end + idx)
+        .unwrap_or(code.len());
+
+    String::from_utf8_lossy(&code[start..end])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+
+/// A set of accepted lint matches.
+#[derive(Debug)]
+pub struct Baseline {
+    /// The path to the baseline file.
+    path: PathBuf,
+    /// The directory relative to which paths are recorded.
+    dir: PathBuf,
+    /// The number of accepted matches for each fingerprint.
+    entries: BTreeMap<Fingerprint, usize>,
+    /// The paths of all files checked against the baseline.
+    checked: HashSet<String>,
+}
+
+impl Baseline {
+    /// Create an empty baseline, to be stored at `path`.
+    pub fn new(path: &Path) -> Result<Self> {
+        let dir = absolute(path)
+            .with_context(|| format!("failed to make `{}` absolute", path.display()))?;
+        // SANITY: An absolute path to a file will always have a parent.
+        let dir = dir.parent().unwrap().to_path_buf();
+
+        let slf = Self {
+            path: path.to_path_buf(),
+            dir,
+            entries: BTreeMap::new(),
+            checked: HashSet::new(),
+        };
+        Ok(slf)
+    }
+
+    /// Load the baseline stored at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = read_to_string(path)
+            .with_context(|| format!("failed to read baseline `{}`", path.display()))?;
+        let RawBaseline { version, entries } = serde_json::from_str::<RawBaseline>(&content)
+            .with_context(|| format!("failed to parse baseline `{}`", path.display()))?;
+        ensure!(
+            version == VERSION,
+            "baseline `{}` has unsupported version {version}",
+            path.display()
+        );
+
+        let mut slf = Self::new(path)?;
+        for RawEntry { fingerprint, count } in entries {
+            *slf.entries.entry(fingerprint).or_default() += count;
+        }
+        Ok(slf)
+    }
+
+    /// Store the baseline in its file.
+    pub fn save(&self) -> Result<()> {
+        let path = &self.path;
+        let entries = self
+            .entries
+            .iter()
+            .map(|(fingerprint, count)| RawEntry {
+                fingerprint: fingerprint.clone(),
+                count: *count,
+            })
+            .collect();
+        let baseline = RawBaseline {
+            version: VERSION,
+            entries,
+        };
+
+        let file = File::create(path)
+            .with_context(|| format!("failed to create baseline `{}`", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let () = serde_json::to_writer_pretty(&mut writer, &baseline)
+            .with_context(|| format!("failed to write baseline `{}`", path.display()))?;
+        let () = writeln!(writer)?;
+        let () = writer.flush()?;
+        Ok(())
+    }
+
+    /// Retrieve the path to the baseline file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Retrieve the path of `src_path` as recorded in the baseline.
+    fn relative_path(&self, src_path: &Path) -> String {
+        absolute(src_path)
+            .ok()
+            .and_then(|path| {
+                path.strip_prefix(&self.dir)
+                    .ok()
+                    .map(|path| path.display().to_string())
+            })
+            .unwrap_or_else(|| src_path.display().to_string())
+    }
+
+    /// Add `matches`, found in `code` of the file at `src_path`, to the
+    /// baseline.
+    pub fn add(&mut self, src_path: &Path, code: &[u8], matches: &[LintMatch]) {
+        let path = self.relative_path(src_path);
+        for m in matches {
+            let fingerprint = Fingerprint {
+                lint: m.lint_name.clone(),
+                path: path.clone(),
+                snippet: normalize_snippet(code, &m.range),
+            };
+            *self.entries.entry(fingerprint).or_default() += 1;
+        }
+    }
+
+    /// Remove all `matches`, found in `code` of the file at `src_path`,
+    /// that are accepted by the baseline.
+    pub fn filter(
+        &mut self,
+        src_path: &Path,
+        code: &[u8],
+        matches: Vec<LintMatch>,
+    ) -> Vec<LintMatch> {
+        let path = self.relative_path(src_path);
+        let matches = matches
+            .into_iter()
+            .filter(|m| {
+                let fingerprint = Fingerprint {
+                    lint: m.lint_name.clone(),
+                    path: path.clone(),
+                    snippet: normalize_snippet(code, &m.range),
+                };
+                match self.entries.get_mut(&fingerprint) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    },
+                    _ => true,
+                }
+            })
+            .collect();
+        let _inserted = self.checked.insert(path);
+        matches
+    }
+
+    /// Retrieve the baseline entries for checked files that did not
+    /// match anymore, along with the number of missing matches.
+    pub fn stale(&self) -> impl Iterator<Item = (&Fingerprint, usize)> {
+        self.entries
+            .iter()
+            .filter(|(fingerprint, count)| **count > 0 && self.checked.contains(&fingerprint.path))
+            .map(|(fingerprint, count)| (fingerprint, *count))
+    }
+
+    /// Retrieve the total number of matches recorded in the baseline.
+    pub fn len(&self) -> usize {
+        self.entries.values().sum()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::write;
+
+    use bpflint::Level;
+    use bpflint::Point;
+
+    use indoc::indoc;
+
+    use tempfile::NamedTempFile;
+
+
+    /// Create a lint match for `text` inside of `code`.
+    fn r#match(lint_name: &str, code: &str, text: &str, nth: usize) -> LintMatch {
+        let (start, _) = code.match_indices(text).nth(nth).unwrap();
+        LintMatch {
+            lint_name: lint_name.to_string(),
+            message: String::new(),
+            range: Range {
+                bytes: start..start + text.len(),
+                start_point: Point::default(),
+                end_point: Point::default(),
+            },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
+        }
+    }
+
+    /// Check that snippets are normalized as expected.
+    #[test]
+    fn snippet_normalization() {
+        let code = indoc! { r#"
+            int main(void) {
+              	bpf_probe_read(a,   b, c);
+            }
+        "# };
+        let m = r#match("probe-read", code, "bpf_probe_read", 0);
+        assert_eq!(
+            normalize_snippet(code.as_bytes(), &m.range),
+            "bpf_probe_read(a, b, c);"
+        );
+
+        let m = r#match("foo", code, "{\n", 0);
+        assert_eq!(
+            normalize_snippet(code.as_bytes(), &m.range),
+            "int main(void) { bpf_probe_read(a, b, c);"
+        );
+    }
+
+    /// Check that baselined matches are filtered and that fixed ones
+    /// are detected, even after code got shifted around.
+    #[test]
+    fn match_filtering() {
+        let code = indoc! { r#"
+            bpf_probe_read(a, b, c);
+            bpf_probe_read(a, b, c);
+            bpf_get_current_task();
+        "# };
+        let matches = [
+            r#match("probe-read", code, "bpf_probe_read", 0),
+            r#match("probe-read", code, "bpf_probe_read", 1),
+            r#match("get-current-task", code, "bpf_get_current_task", 0),
+        ];
+
+        let file = NamedTempFile::new().unwrap();
+        let mut baseline = Baseline::new(file.path()).unwrap();
+        let src_path = file.path().with_file_name("test.bpf.c");
+        let () = baseline.add(&src_path, code.as_bytes(), &matches);
+        assert_eq!(baseline.len(), 3);
+        let () = baseline.save().unwrap();
+
+        let code = indoc! { r#"
+            int x;
+
+            bpf_probe_read(a, b, c);
+            bpf_get_current_task();
+            bpf_probe_read(a, b, d);
+        "# };
+        let matches = vec![
+            r#match("probe-read", code, "bpf_probe_read", 0),
+            r#match("get-current-task", code, "bpf_get_current_task", 0),
+            r#match("probe-read", code, "bpf_probe_read", 1),
+        ];
+
+        let mut baseline = Baseline::load(file.path()).unwrap();
+        assert_eq!(baseline.stale().count(), 0);
+        let matches = baseline.filter(&src_path, code.as_bytes(), matches);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].range.bytes.start,
+            code.rfind("bpf_probe_read").unwrap()
+        );
+
+        let stale = baseline.stale().collect::<Vec<_>>();
+        assert_eq!(
+            stale,
+            vec![(
+                &Fingerprint {
+                    lint: "probe-read".to_string(),
+                    path: "test.bpf.c".to_string(),
+                    snippet: "bpf_probe_read(a, b, c);".to_string(),
+                },
+                1
+            )]
+        );
+    }
+
+    /// Check that we fail to load baselines with an unsupported
+    /// version.
+    #[test]
+    fn version_checking() {
+        let file = NamedTempFile::new().unwrap();
+        let () = write(file.path(), r#"{"version": 2, "entries": []}"#).unwrap();
+        let err = Baseline::load(file.path()).unwrap_err();
+        assert!(err.to_string().contains("unsupported version 2"), "{err}");
+    }
+}
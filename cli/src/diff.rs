@@ -0,0 +1,296 @@
+//! Parsing of unified diffs for restricting lint matches to changed
+//! lines.
+
+use std::collections::HashMap;
+use std::ops;
+use std::path::Path;
+use std::path::PathBuf;
+use std::path::absolute;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::bail;
+
+use bpflint::LintMatch;
+
+
+/// Extract the path from a `---` or `+++` file header line, minus any
+/// trailing timestamp.
+fn header_path(header: &str) -> &str {
+    let path = header.split('\t').next().unwrap_or(header).trim_end();
+    path.strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+        .unwrap_or(path)
+}
+
+
+/// Parse a hunk header of the form `@@ -l[,s] +l[,s] @@`, returning the
+/// number of old lines as well as the first and number of new lines.
+fn parse_hunk_header(header: &str) -> Result<(usize, usize, usize)> {
+    fn parse_range(range: &str) -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    }
+
+    let ranges = header
+        .strip_prefix("@@ ")
+        .and_then(|header| header.split_once(" @@"))
+        .map(|(ranges, _section)| ranges);
+    let result = ranges
+        .and_then(|ranges| ranges.split_once(' '))
+        .and_then(|(old, new)| {
+            let (_old_start, old_count) = parse_range(old.strip_prefix('-')?)?;
+            let (new_start, new_count) = parse_range(new.strip_prefix('+')?)?;
+            Some((old_count, new_start, new_count))
+        });
+
+    match result {
+        Some(result) => Ok(result),
+        None => bail!("encountered invalid hunk header `{header}`"),
+    }
+}
+
+
+/// The lines added or modified by a unified diff.
+#[derive(Debug, Default)]
+pub struct Diff {
+    /// The changed lines of each file, as sorted ranges of zero based
+    /// rows, keyed by absolute path.
+    files: HashMap<PathBuf, Vec<ops::Range<usize>>>,
+}
+
+impl Diff {
+    /// Parse a unified diff, as produced by `git diff` or `diff -u`.
+    ///
+    /// Paths are interpreted relative to the current working directory.
+    /// `a/` and `b/` prefixes, as used by `git diff`, are stripped.
+    pub fn parse(diff: &str) -> Result<Self> {
+        let mut slf = Self::default();
+        let mut lines = diff.lines().enumerate();
+        let mut old_path = None;
+        let mut rows = None;
+
+        while let Some((idx, line)) = lines.next() {
+            if let Some(header) = line.strip_prefix("--- ") {
+                old_path = Some(header_path(header));
+            } else if let Some(header) = line.strip_prefix("+++ ") {
+                let path = header_path(header);
+                let git = old_path
+                    .take()
+                    .map(|old| old.starts_with("a/") || old == "/dev/null")
+                    .unwrap_or(false);
+                rows = if path == "/dev/null" {
+                    None
+                } else {
+                    let path = if git {
+                        path.strip_prefix("b/").unwrap_or(path)
+                    } else {
+                        path
+                    };
+                    let path = absolute(path)
+                        .with_context(|| format!("failed to make `{path}` absolute"))?;
+                    Some(slf.files.entry(path).or_default())
+                };
+            } else if line.starts_with("@@ ") {
+                let (mut old_count, new_start, mut new_count) = parse_hunk_header(line)
+                    .with_context(|| format!("failed to parse diff line {}", idx + 1))?;
+                let mut row = new_start.saturating_sub(1);
+
+                while old_count > 0 || new_count > 0 {
+                    let Some((idx, line)) = lines.next() else {
+                        bail!("diff ends in the middle of a hunk")
+                    };
+                    let (old, new) = match line.as_bytes().first() {
+                        Some(b'+') => (0, 1),
+                        Some(b'-') => (1, 0),
+                        // Some tools strip trailing white space, turning
+                        // empty context lines into empty lines.
+                        Some(b' ') | None => (1, 1),
+                        Some(b'\\') => (0, 0),
+                        Some(..) => bail!("encountered invalid hunk line {}: `{line}`", idx + 1),
+                    };
+
+                    let (Some(old_left), Some(new_left)) =
+                        (old_count.checked_sub(old), new_count.checked_sub(new))
+                    else {
+                        bail!("hunk ending at line {} is longer than announced", idx + 1)
+                    };
+                    (old_count, new_count) = (old_left, new_left);
+
+                    if (old, new) == (0, 1) {
+                        if let Some(rows) = &mut rows {
+                            match rows.last_mut() {
+                                Some(last) if last.end == row => last.end += 1,
+                                _ => rows.push(row..row + 1),
+                            }
+                        }
+                    }
+                    row += new;
+                }
+            }
+        }
+        Ok(slf)
+    }
+
+    /// Check whether the diff covers the file at `path`.
+    pub fn contains(&self, path: &Path) -> bool {
+        absolute(path)
+            .map(|path| self.files.contains_key(&path))
+            .unwrap_or(false)
+    }
+
+    /// Remove all `matches` found in the file at `path` that do not
+    /// overlap with any line added or modified by the diff.
+    pub fn filter(&self, path: &Path, mut matches: Vec<LintMatch>) -> Vec<LintMatch> {
+        let rows = absolute(path)
+            .ok()
+            .and_then(|path| self.files.get(&path))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let () = matches.retain(|m| {
+            rows.iter().any(|rows| {
+                rows.start <= m.range.end_point.row && m.range.start_point.row < rows.end
+            })
+        });
+        matches
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::Level;
+    use bpflint::Point;
+    use bpflint::Range;
+
+    use indoc::indoc;
+
+
+    /// Create a lint match spanning the given (zero based) rows.
+    fn r#match(start_row: usize, end_row: usize) -> LintMatch {
+        LintMatch {
+            lint_name: "foo".to_string(),
+            message: String::new(),
+            range: Range {
+                bytes: 0..0,
+                start_point: Point {
+                    row: start_row,
+                    col: 0,
+                },
+                end_point: Point {
+                    row: end_row,
+                    col: 1,
+                },
+            },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
+        }
+    }
+
+    /// Check that we can parse hunk headers.
+    #[test]
+    fn hunk_header_parsing() {
+        assert_eq!(parse_hunk_header("@@ -1,3 +1,4 @@").unwrap(), (3, 1, 4));
+        assert_eq!(
+            parse_hunk_header("@@ -10 +12,0 @@ int main(void)").unwrap(),
+            (1, 12, 0)
+        );
+
+        let err = parse_hunk_header("@@ -1,3 @@").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "encountered invalid hunk header `@@ -1,3 @@`"
+        );
+        assert!(parse_hunk_header("@@ -a +1 @@").is_err());
+    }
+
+    /// Check that changed lines are extracted from a `git diff` style
+    /// diff.
+    #[test]
+    fn diff_parsing() {
+        let diff = indoc! { r#"
+            diff --git a/src/foo.bpf.c b/src/foo.bpf.c
+            index 1234567..89abcde 100644
+            --- a/src/foo.bpf.c
+            +++ b/src/foo.bpf.c
+            @@ -2,3 +2,5 @@ int x;
+             int a;
+            -int b;
+            +int b = 1;
+            +int c;
+            +++ int d;
+             int e;
+            @@ -20,2 +21,2 @@
+             int y;
+            -int z;
+            +int z = 2;
+            \ No newline at end of file
+            diff --git a/src/old.bpf.c b/src/old.bpf.c
+            deleted file mode 100644
+            --- a/src/old.bpf.c
+            +++ /dev/null
+            @@ -1 +0,0 @@
+            -int foo;
+            --- bar.bpf.c	2025-01-01 00:00:00.000000000 +0000
+            +++ b/bar.bpf.c	2025-01-02 00:00:00.000000000 +0000
+            @@ -0,0 +1 @@
+            +int bar;
+        "# };
+        let diff = Diff::parse(diff).unwrap();
+        let foo = absolute("src/foo.bpf.c").unwrap();
+        assert_eq!(diff.files[&foo], vec![2..5, 21..22]);
+        assert!(diff.contains(Path::new("./src/foo.bpf.c")));
+        assert!(!diff.contains(Path::new("src/old.bpf.c")));
+        // Without the `a/` prefix on the old path we keep the `b/`.
+        assert!(diff.contains(Path::new("b/bar.bpf.c")));
+
+        let matches = vec![
+            r#match(0, 0),
+            r#match(1, 2),
+            r#match(5, 6),
+            r#match(4, 4),
+            r#match(19, 20),
+            r#match(21, 21),
+        ];
+        let matches = diff.filter(Path::new("src/foo.bpf.c"), matches);
+        let rows = matches
+            .iter()
+            .map(|m| m.range.start_point.row)
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![1, 4, 21]);
+
+        let matches = diff.filter(Path::new("src/baz.bpf.c"), vec![r#match(0, 0)]);
+        assert!(matches.is_empty());
+    }
+
+    /// Check that we detect malformed hunks.
+    #[test]
+    fn invalid_diff_parsing() {
+        let diff = indoc! { r#"
+            --- a/foo.bpf.c
+            +++ b/foo.bpf.c
+            @@ -1,2 +1,2 @@
+             int a;
+        "# };
+        let err = Diff::parse(diff).unwrap_err();
+        assert_eq!(err.to_string(), "diff ends in the middle of a hunk");
+
+        let diff = indoc! { r#"
+            --- a/foo.bpf.c
+            +++ b/foo.bpf.c
+            @@ -1 +1 @@
+            -int a;
+            -int b;
+        "# };
+        let err = Diff::parse(diff).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hunk ending at line 5 is longer than announced"
+        );
+    }
+}
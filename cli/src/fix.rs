@@ -0,0 +1,113 @@
+//! Helpers for reporting fixes applied to source files.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use anyhow::Result;
+
+use similar::TextDiff;
+
+use bpflint::LintMatch;
+
+
+/// Write a unified diff between the `old` and `new` versions of the
+/// source file at `path`.
+pub fn write_diff(path: &Path, old: &[u8], new: &[u8], writer: &mut dyn io::Write) -> Result<()> {
+    let old = String::from_utf8_lossy(old);
+    let new = String::from_utf8_lossy(new);
+    let path = path.display().to_string();
+    let diff = TextDiff::from_lines(old.as_ref(), new.as_ref());
+    write!(writer, "{}", diff.unified_diff().header(&path, &path))?;
+    Ok(())
+}
+
+
+/// Create a human readable summary of the provided fixed lint matches.
+pub fn describe_fixes(fixed: &[LintMatch]) -> String {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for m in fixed {
+        *counts.entry(&m.lint_name).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(lint_name, count)| format!("{lint_name} (x{count})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::Level;
+    use bpflint::Range;
+
+    use indoc::indoc;
+
+
+    /// Check that we create proper unified diffs.
+    #[test]
+    fn diff_writing() {
+        let old = indoc! { r#"
+            struct {
+                __uint(type, BPF_MAP_TYPE_HASH);
+                __uint(key_size, sizeof(u32));
+            } events SEC(".maps");
+        "# };
+        let new = indoc! { r#"
+            struct {
+                __uint(type, BPF_MAP_TYPE_HASH);
+                __type(key, u32);
+            } events SEC(".maps");
+        "# };
+
+        let mut r = Vec::new();
+        let () = write_diff(
+            Path::new("src/foo.bpf.c"),
+            old.as_bytes(),
+            new.as_bytes(),
+            &mut r,
+        )
+        .unwrap();
+        let expected = indoc! { r#"
+            --- src/foo.bpf.c
+            +++ src/foo.bpf.c
+            @@ -1,4 +1,4 @@
+             struct {
+                 __uint(type, BPF_MAP_TYPE_HASH);
+            -    __uint(key_size, sizeof(u32));
+            +    __type(key, u32);
+             } events SEC(".maps");
+        "# };
+        assert_eq!(String::from_utf8(r).unwrap(), expected);
+    }
+
+    /// Check that fixes are summarized as expected.
+    #[test]
+    fn fix_description() {
+        let m = |lint_name: &str| LintMatch {
+            lint_name: lint_name.to_string(),
+            message: String::new(),
+            range: Range {
+                bytes: 0..0,
+                start_point: Default::default(),
+                end_point: Default::default(),
+            },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
+        };
+        let fixed = [
+            m("untyped-map-member"),
+            m("probe-read"),
+            m("untyped-map-member"),
+        ];
+        assert_eq!(
+            describe_fixes(&fixed),
+            "probe-read (x1), untyped-map-member (x2)"
+        );
+    }
+}
@@ -1,24 +1,36 @@
 //! A linter for BPF C code.
 
 mod args;
+mod baseline;
+mod diff;
+mod fix;
+mod walk;
 
+use std::collections::BTreeMap;
 use std::env::var_os;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::fs::read;
+use std::fs::write;
 use std::io;
 use std::io::IsTerminal as _;
+use std::io::Read as _;
 use std::io::Write as _;
 use std::io::stderr;
-use std::ops::Not as _;
 use std::path::Path;
 use std::process::ExitCode;
 use std::process::Termination;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::thread;
 
 use anyhow::Context as _;
 use anyhow::Error;
 use anyhow::Result;
+use anyhow::anyhow;
 
 use clap::Parser as _;
 
@@ -28,14 +40,89 @@ use tracing_subscriber::FmtSubscriber;
 use tracing_subscriber::filter::EnvFilter;
 use tracing_subscriber::fmt::time::ChronoLocal;
 
+use bpflint::Fixed;
+use bpflint::Level as LintLevel;
 use bpflint::LintMatch;
 use bpflint::LintOpts;
+use bpflint::Linter;
 use bpflint::Point;
 use bpflint::Range;
-use bpflint::builtin_lints;
-use bpflint::lint_custom_opts;
+use bpflint::config::Linters;
+use bpflint::config::Selection;
+use bpflint::json;
+use bpflint::sarif;
 use bpflint::terminal;
 
+use crate::args::Format;
+use crate::baseline::Baseline;
+use crate::diff::Diff;
+use crate::fix::describe_fixes;
+use crate::fix::write_diff;
+use crate::walk::Source;
+use crate::walk::Walker;
+
+
+/// The path used on the command line to refer to standard input.
+const STDIN_PATH: &str = "-";
+/// The name under which source files with an unexpected extension are
+/// reported.
+const BOGUS_FILE_EXTENSION: &str = "bogus-file-extension";
+
+
+/// The outcome of checking a single source file.
+enum Outcome {
+    /// The file was linted, resulting in the contained matches.
+    Linted(Vec<LintMatch>),
+    /// Fixes were determined for the file.
+    Fixed(Fixed),
+}
+
+
+/// A source file to check, along with the linter to use.
+struct Check<'src> {
+    /// The source file.
+    src: &'src Source,
+    /// The path under which to report the file.
+    src_path: &'src Path,
+    /// The linter to check the file with.
+    linter: Arc<Linter>,
+}
+
+impl Check<'_> {
+    /// Read the source file and lint or, if `fix` is set, fix it,
+    /// returning the original source code along with the outcome.
+    fn run(&self, fix: bool) -> Result<(Vec<u8>, Outcome)> {
+        let Self {
+            src,
+            src_path,
+            linter,
+        } = self;
+
+        let code = if src.path == Path::new(STDIN_PATH) {
+            let mut code = Vec::new();
+            let _count = io::stdin()
+                .read_to_end(&mut code)
+                .context("failed to read source code from stdin")?;
+            code
+        } else {
+            read(&src.path).with_context(|| format!("failed to read `{}`", src.path.display()))?
+        };
+
+        let outcome = if fix {
+            let fixed = linter
+                .fix(&code)
+                .with_context(|| format!("failed to fix `{}`", src_path.display()))?;
+            Outcome::Fixed(fixed)
+        } else {
+            let matches = linter
+                .lint(&code)
+                .with_context(|| format!("failed to lint `{}`", src_path.display()))?;
+            Outcome::Linted(matches)
+        };
+        Ok((code, outcome))
+    }
+}
+
 
 fn has_bpf_c_ext(path: &Path) -> bool {
     if let Some(file_name) = path.file_name() {
@@ -79,13 +166,30 @@ fn main_impl() -> Result<(), ExitError> {
     let args = args::Args::parse();
     let args::Args {
         srcs,
+        stdin_filename,
+        includes,
+        excludes,
         before,
         after,
         context,
+        format,
         color,
         print_lints,
         verbosity,
         kernel_version,
+        lint_dirs,
+        allow,
+        warn,
+        deny,
+        parse_errors,
+        unused_directives,
+        unjustified_directives,
+        fix,
+        fix_dry_run,
+        baseline,
+        write_baseline,
+        diff,
+        jobs,
     } = args;
 
     let mut opts = terminal::Opts {
@@ -99,11 +203,6 @@ fn main_impl() -> Result<(), ExitError> {
         opts.extra_lines.1 = after;
     }
 
-    let lint_opts = LintOpts {
-        kernel_version,
-        ..Default::default()
-    };
-
     let level = match verbosity {
         0 => Level::WARN,
         1 => Level::INFO,
@@ -132,40 +231,277 @@ fn main_impl() -> Result<(), ExitError> {
     let mut stdout = stdout.lock();
 
     let m_ext_is_c = LintMatch {
-        lint_name: "bogus-file-extension".to_string(),
+        lint_name: BOGUS_FILE_EXTENSION.to_string(),
         message: "by convention BPF C code should use the file extension '.bpf.c'".to_string(),
         range: Range {
             bytes: 0..0,
             start_point: Point { row: 0, col: 0 },
             end_point: Point { row: 0, col: 0 },
         },
+        min_kernel_version: None,
+        level: LintLevel::Warn,
+        suggestion: None,
+    };
+
+    let selection = Selection {
+        allow,
+        warn,
+        deny,
+        synthetic: vec![BOGUS_FILE_EXTENSION.to_string()],
+        ..Default::default()
+    };
+    let lint_opts = LintOpts {
+        kernel_version,
+        parse_errors: parse_errors.map(LintLevel::from),
+        unused_directives: unused_directives.map(LintLevel::from),
+        unjustified_directives: unjustified_directives.map(LintLevel::from),
+        ..Default::default()
     };
+    let mut linters = Linters::new(&lint_dirs, selection, lint_opts)?;
 
     if print_lints {
-        for lint in builtin_lints() {
+        // Configuration files are discovered based on source file
+        // paths, so pretend to check a file in the current directory.
+        for lint in linters.available(Path::new(STDIN_PATH))? {
             writeln!(&mut stdout, "{}", lint.name)?;
         }
         Ok(())
     } else {
+        let mut baseline = match (&baseline, &write_baseline) {
+            (Some(path), _) => Some(Baseline::load(path)?),
+            (None, Some(path)) => Some(Baseline::new(path)?),
+            (None, None) => None,
+        };
         let mut result = Ok(());
-        for src_path in srcs.iter().flatten() {
-            let code = read(src_path)
-                .with_context(|| format!("failed to read `{}`", src_path.display()))?;
+        let mut reports = Vec::new();
+        let walker = Walker::new(&includes, &excludes)?;
+        let srcs = srcs
+            .iter()
+            .flatten()
+            .map(|path| walker.expand(path))
+            .collect::<Result<Vec<_>>>()?;
+
+        if fix
+            && srcs
+                .iter()
+                .flatten()
+                .any(|src| src.path == Path::new(STDIN_PATH))
+        {
+            return Err(ExitError::from(anyhow!(
+                "cannot fix source code read from stdin in place; use `--fix-dry-run` instead"
+            )))
+        }
 
-            let mut first = true;
-            let match_ext = has_bpf_c_ext(src_path).not().then_some(&m_ext_is_c);
-            let matches = lint_custom_opts(&code, builtin_lints(), &lint_opts)
-                .with_context(|| format!("failed to lint `{}`", src_path.display()))?;
-            for m in match_ext.into_iter().chain(matches.iter()) {
-                if !first {
-                    writeln!(&mut stdout)?;
-                } else {
-                    first = false;
+        let diff = if let Some(path) = &diff {
+            let diff = if path == Path::new(STDIN_PATH) {
+                if srcs
+                    .iter()
+                    .flatten()
+                    .any(|src| src.path == Path::new(STDIN_PATH))
+                {
+                    return Err(ExitError::from(anyhow!(
+                        "cannot read both the diff and source code from stdin"
+                    )))
+                }
+
+                let mut diff = Vec::new();
+                let _count = io::stdin()
+                    .read_to_end(&mut diff)
+                    .context("failed to read diff from stdin")?;
+                diff
+            } else {
+                read(path).with_context(|| format!("failed to read diff `{}`", path.display()))?
+            };
+            Some(Diff::parse(&String::from_utf8_lossy(&diff)).context("failed to parse diff")?)
+        } else {
+            None
+        };
+
+        let mut checks = Vec::new();
+        for src in srcs.iter().flatten() {
+            let src_path = if src.path == Path::new(STDIN_PATH) {
+                stdin_filename.as_deref().unwrap_or(Path::new("<stdin>"))
+            } else {
+                src.path.as_path()
+            };
+
+            if let Some(diff) = &diff {
+                if !diff.contains(src_path) {
+                    continue
                 }
+            }
+
+            let check = Check {
+                src,
+                src_path,
+                linter: Arc::clone(linters.get(src_path)?),
+            };
+            let () = checks.push(check);
+        }
+
+        let next = AtomicUsize::new(0);
+        let () = thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            for _ in 0..jobs.get() {
+                let sender = sender.clone();
+                let (checks, next) = (&checks, &next);
+                let _handle = scope.spawn(move || {
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(check) = checks.get(idx) else { break };
+                        let checked = check.run(fix || fix_dry_run);
+                        if sender.send((idx, checked)).is_err() {
+                            break
+                        }
+                    }
+                });
+            }
+            let () = drop(sender);
+
+            // Workers may finish files out of order, but we report them
+            // in the order in which they were provided, to keep output
+            // deterministic.
+            let mut pending = BTreeMap::new();
+            let mut reported = 0;
+            for (idx, checked) in receiver {
+                let _prev = pending.insert(idx, checked);
+                while let Some(checked) = pending.remove(&reported) {
+                    let Check {
+                        src,
+                        src_path,
+                        linter,
+                    } = &checks[reported];
+                    let src_path = *src_path;
+                    reported += 1;
+
+                    let (code, outcome) = checked?;
+                    let (code, matches) = match outcome {
+                        Outcome::Fixed(Fixed {
+                            code: fixed_code,
+                            fixed,
+                            matches,
+                        }) => {
+                            if fix_dry_run {
+                                if !fixed.is_empty() {
+                                    let () = write_diff(src_path, &code, &fixed_code, &mut stdout)?;
+                                    if result.is_ok() {
+                                        result = Err(ExitError::ExitCode(ExitCode::FAILURE));
+                                    }
+                                }
+                                continue
+                            }
+
+                            if !fixed.is_empty() {
+                                let () = write(&src.path, &fixed_code).with_context(|| {
+                                    format!("failed to write `{}`", src.path.display())
+                                })?;
+                                let _result = writeln!(
+                                    stderr(),
+                                    "fixed `{}`: {}",
+                                    src_path.display(),
+                                    describe_fixes(&fixed)
+                                );
+                            }
+                            (fixed_code, matches)
+                        },
+                        Outcome::Linted(matches) => (code, matches),
+                    };
+
+                    // Files discovered in directories were selected
+                    // based on user provided globs, so we don't second
+                    // guess them. For code from stdin we can only check
+                    // if we got a file name.
+                    let is_stdin = src.path == Path::new(STDIN_PATH);
+                    let check_ext = src.explicit && (!is_stdin || stdin_filename.is_some());
+                    let ext_level = linter
+                        .opts()
+                        .levels
+                        .get(BOGUS_FILE_EXTENSION)
+                        .copied()
+                        .unwrap_or_default();
+                    let match_ext =
+                        (check_ext && ext_level != LintLevel::Allow && !has_bpf_c_ext(src_path))
+                            .then(|| LintMatch {
+                                level: ext_level,
+                                ..m_ext_is_c.clone()
+                            });
+                    let matches = match_ext.into_iter().chain(matches).collect::<Vec<_>>();
+                    let matches = match &mut baseline {
+                        Some(baseline) if write_baseline.is_some() => {
+                            let () = baseline.add(src_path, &code, &matches);
+                            continue
+                        },
+                        Some(baseline) => baseline.filter(src_path, &code, matches),
+                        None => matches,
+                    };
+                    let matches = match &diff {
+                        Some(diff) => diff.filter(src_path, matches),
+                        None => matches,
+                    };
+
+                    if matches.iter().any(|m| m.level == LintLevel::Deny) && result.is_ok() {
+                        result = Err(ExitError::ExitCode(ExitCode::FAILURE));
+                    }
+
+                    match format {
+                        Format::Text => {
+                            let mut first = true;
+                            for m in &matches {
+                                if !first {
+                                    writeln!(&mut stdout)?;
+                                } else {
+                                    first = false;
+                                }
+
+                                let () =
+                                    terminal::report_opts(m, &code, src_path, &opts, &mut stdout)?;
+                            }
+                        },
+                        Format::Json | Format::Sarif => {
+                            let () = reports.push((src_path.to_path_buf(), code, matches));
+                        },
+                    }
+                }
+            }
+            Result::<()>::Ok(())
+        })?;
+
+        match format {
+            Format::Text => (),
+            Format::Json => {
+                let reports = reports
+                    .iter()
+                    .map(|(path, _code, matches)| (path.as_path(), matches));
+                let () = json::report(reports, &mut stdout)?;
+            },
+            Format::Sarif => {
+                let reports = reports
+                    .iter()
+                    .map(|(path, code, matches)| (path.as_path(), code.as_slice(), matches));
+                let () = sarif::report(linters.lints(), reports, &mut stdout)?;
+            },
+        }
 
-                let () = terminal::report_opts(m, &code, src_path, &opts, &mut stdout)?;
-                if result.is_ok() {
-                    result = Err(ExitError::ExitCode(ExitCode::FAILURE));
+        if let Some(baseline) = &baseline {
+            let path = baseline.path();
+            if write_baseline.is_some() {
+                let () = baseline.save()?;
+                let _result = writeln!(
+                    stderr(),
+                    "recorded {} lint match(es) in baseline `{}`",
+                    baseline.len(),
+                    path.display()
+                );
+            } else {
+                for (fingerprint, count) in baseline.stale() {
+                    let _result = writeln!(
+                        stderr(),
+                        "baseline `{}` lists {count} match(es) of `{}` in `{}` that no longer occur(s): {}",
+                        path.display(),
+                        fingerprint.lint,
+                        fingerprint.path,
+                        fingerprint.snippet,
+                    );
                 }
             }
         }
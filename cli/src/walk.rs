@@ -0,0 +1,223 @@
+//! Discovery of source files to lint inside of directories.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use anyhow::Result;
+
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+
+use ignore::WalkBuilder;
+
+
+/// The glob matching files to lint when walking a directory, in
+/// addition to any user provided ones.
+const DEFAULT_INCLUDE: &str = "*.bpf.c";
+
+
+/// A source file to lint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Source {
+    /// The path to the file.
+    pub path: PathBuf,
+    /// Whether the file was provided explicitly by the user, as
+    /// opposed to having been discovered inside a directory.
+    pub explicit: bool,
+}
+
+
+fn build_glob_set<'g, G>(globs: G) -> Result<GlobSet>
+where
+    G: IntoIterator<Item = &'g str>,
+{
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob).with_context(|| format!("encountered invalid glob `{glob}`"))?;
+        let _builder = builder.add(glob);
+    }
+    let set = builder.build()?;
+    Ok(set)
+}
+
+
+/// A type for expanding user provided paths into the source files to
+/// lint.
+#[derive(Debug)]
+pub struct Walker {
+    /// Globs matching the files to include.
+    includes: GlobSet,
+    /// Globs matching the files and directories to exclude.
+    excludes: GlobSet,
+}
+
+impl Walker {
+    /// Create a new [`Walker`] including files matching any of the
+    /// `includes` globs (in addition to `*.bpf.c` files) and excluding
+    /// files and directories matching any of the `excludes` globs.
+    ///
+    /// Globs are matched against paths relative to the directory
+    /// being walked.
+    pub fn new(includes: &[String], excludes: &[String]) -> Result<Self> {
+        let includes = [DEFAULT_INCLUDE]
+            .into_iter()
+            .chain(includes.iter().map(String::as_str));
+        let slf = Self {
+            includes: build_glob_set(includes)?,
+            excludes: build_glob_set(excludes.iter().map(String::as_str))?,
+        };
+        Ok(slf)
+    }
+
+    /// Expand `path` into the source files to lint.
+    ///
+    /// A directory is walked recursively, honoring `.gitignore` files
+    /// and similar, and all included files are reported in a stable
+    /// order. Anything else is reported as is.
+    pub fn expand(&self, path: &Path) -> Result<Vec<Source>> {
+        if !path.is_dir() {
+            let src = Source {
+                path: path.to_path_buf(),
+                explicit: true,
+            };
+            return Ok(vec![src])
+        }
+
+        let root = path.to_path_buf();
+        let excludes = self.excludes.clone();
+        let walk = WalkBuilder::new(path)
+            .require_git(false)
+            .sort_by_file_name(OsStr::cmp)
+            .filter_entry(move |entry| {
+                // The directory being walked itself is never excluded.
+                if entry.depth() == 0 {
+                    return true
+                }
+                let rel_path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+                !excludes.is_match(rel_path)
+            })
+            .build();
+
+        let mut srcs = Vec::new();
+        for result in walk {
+            let entry =
+                result.with_context(|| format!("failed to walk directory `{}`", path.display()))?;
+            if !entry.file_type().is_some_and(|ty| ty.is_file()) {
+                continue
+            }
+
+            let rel_path = entry.path().strip_prefix(path).unwrap_or(entry.path());
+            if self.includes.is_match(rel_path) {
+                let src = Source {
+                    path: entry.into_path(),
+                    explicit: false,
+                };
+                let () = srcs.push(src);
+            }
+        }
+        Ok(srcs)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::create_dir_all;
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+
+    /// Create a directory hierarchy with a couple of files for testing
+    /// purposes.
+    fn create_tree() -> TempDir {
+        let root = TempDir::new().unwrap();
+        for dir in ["src/nested", "vendor", "build"] {
+            let () = create_dir_all(root.path().join(dir)).unwrap();
+        }
+        for file in [
+            "top.bpf.c",
+            "src/a.bpf.c",
+            "src/a.bpf.h",
+            "src/b.c",
+            "src/nested/c.bpf.c",
+            "vendor/d.bpf.c",
+            "build/e.bpf.c",
+        ] {
+            let () = write(root.path().join(file), "").unwrap();
+        }
+        let () = write(root.path().join(".gitignore"), "build/\n").unwrap();
+        root
+    }
+
+    fn rel_paths(root: &Path, srcs: Vec<Source>) -> Vec<String> {
+        srcs.into_iter()
+            .map(|src| {
+                assert!(!src.explicit);
+                src.path
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    /// Check that we discover the expected files when walking a
+    /// directory.
+    #[test]
+    fn directory_walking() {
+        let root = create_tree();
+
+        let walker = Walker::new(&[], &[]).unwrap();
+        let srcs = walker.expand(root.path()).unwrap();
+        assert_eq!(
+            rel_paths(root.path(), srcs),
+            vec![
+                "src/a.bpf.c",
+                "src/nested/c.bpf.c",
+                "top.bpf.c",
+                "vendor/d.bpf.c"
+            ]
+        );
+
+        let walker = Walker::new(
+            &["*.bpf.h".to_string()],
+            &["vendor".to_string(), "**/nested/*".to_string()],
+        )
+        .unwrap();
+        let srcs = walker.expand(root.path()).unwrap();
+        assert_eq!(
+            rel_paths(root.path(), srcs),
+            vec!["src/a.bpf.c", "src/a.bpf.h", "top.bpf.c"]
+        );
+    }
+
+    /// Make sure that paths to files are reported as is.
+    #[test]
+    fn explicit_file() {
+        let root = create_tree();
+        let walker = Walker::new(&[], &["*".to_string()]).unwrap();
+        let path = root.path().join("src").join("b.c");
+        let srcs = walker.expand(&path).unwrap();
+        assert_eq!(
+            srcs,
+            vec![Source {
+                path,
+                explicit: true
+            }]
+        );
+    }
+
+    /// Check that invalid globs are rejected.
+    #[test]
+    fn invalid_glob() {
+        assert!(Walker::new(&["[".to_string()], &[]).is_err());
+        assert!(Walker::new(&[], &["[".to_string()]).is_err());
+    }
+}
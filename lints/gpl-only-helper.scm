@@ -0,0 +1,4 @@
+(call_expression
+    function: (identifier) @helper
+    (#gpl-only-helper? @helper)
+)
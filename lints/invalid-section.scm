@@ -0,0 +1,6 @@
+(function_definition
+    (sec_specifier
+        value: (string_literal) @section
+        (#invalid-section? @section)
+    )
+)
@@ -0,0 +1,9 @@
+(
+    [
+        (return_statement) @exit
+        (function_definition
+            body: (compound_statement "}" @exit)
+        )
+    ]
+    (#ringbuf-leak? @exit)
+)
@@ -0,0 +1,6 @@
+(
+  (call_expression
+    function: (identifier) @helper
+    (#unavailable-helper? @helper)
+  )
+)
@@ -1,6 +1,11 @@
 (preproc_call_expression
     macro_name: (identifier) @__name (#eq? @__name "__uint")
     arg1: (identifier) @__arg1 (#any-of? @__arg1 "key_size" "value_size")
-    (sizeof_expression)
+    [
+      (sizeof_expression type: (type_descriptor) @__type)
+      (sizeof_expression value: (parenthesized_expression . (identifier) @__type .))
+      (sizeof_expression)
+    ]
     (#set! "min_kernel_version" "4.18.0")
+    (#set! "fix" "__type({{__arg1%_size}}, {{__type}})")
 ) @call
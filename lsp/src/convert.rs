@@ -0,0 +1,205 @@
+//! Conversion between bpflint and LSP types.
+
+use std::path::PathBuf;
+
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::NumberOrString;
+use lsp_types::Position;
+use lsp_types::Range as LspRange;
+use lsp_types::Uri;
+
+use url::Url;
+
+use bpflint::Level;
+use bpflint::LintMatch;
+use bpflint::Point;
+use bpflint::Range;
+
+
+/// The source reported for diagnostics.
+pub const SOURCE: &str = "bpflint";
+
+
+/// Convert a `point` referring to byte offset `byte` inside of `text`
+/// into an LSP position.
+///
+/// LSP columns are expressed in terms of UTF-16 code units, whereas our
+/// columns are in terms of bytes.
+pub fn position(text: &str, point: &Point, byte: usize) -> Position {
+    let bytes = text.as_bytes();
+    let byte = byte.min(bytes.len());
+    let line_start = bytes[..byte]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let col = String::from_utf8_lossy(&bytes[line_start..byte])
+        .encode_utf16()
+        .count();
+
+    Position {
+        // LSP does not support more than `u32::MAX` lines or columns
+        // and neither do we, realistically.
+        line: u32::try_from(point.row).unwrap_or(u32::MAX),
+        character: u32::try_from(col).unwrap_or(u32::MAX),
+    }
+}
+
+
+/// Convert an LSP `position` inside of `text` into a byte offset.
+///
+/// Positions beyond the end of a line or of the text are clamped
+/// accordingly.
+pub fn offset(text: &str, position: &Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(idx) => line_start += idx + 1,
+            None => return text.len(),
+        }
+    }
+
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let character = usize::try_from(position.character).unwrap_or(usize::MAX);
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= character {
+            return line_start + idx
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+
+/// Convert a [`Range`] inside of `text` into an LSP range.
+pub fn range(text: &str, range: &Range) -> LspRange {
+    LspRange {
+        start: position(text, &range.start_point, range.bytes.start),
+        end: position(text, &range.end_point, range.bytes.end),
+    }
+}
+
+
+/// Check whether two LSP ranges overlap or touch.
+pub fn overlaps(range1: &LspRange, range2: &LspRange) -> bool {
+    range1.start <= range2.end && range2.start <= range1.end
+}
+
+
+/// Convert a [`LintMatch`] on `text` into an LSP diagnostic.
+pub fn diagnostic(text: &str, r#match: &LintMatch) -> Diagnostic {
+    let LintMatch {
+        lint_name,
+        message,
+        range: match_range,
+        min_kernel_version: _,
+        level,
+        suggestion: _,
+    } = r#match;
+
+    Diagnostic {
+        range: range(text, match_range),
+        severity: Some(match level {
+            Level::Allow => DiagnosticSeverity::HINT,
+            Level::Warn => DiagnosticSeverity::WARNING,
+            Level::Deny => DiagnosticSeverity::ERROR,
+        }),
+        code: Some(NumberOrString::String(lint_name.clone())),
+        source: Some(SOURCE.to_string()),
+        message: message.clone(),
+        ..Default::default()
+    }
+}
+
+
+/// Convert a `file://` URI into a file system path.
+pub fn file_path(uri: &Uri) -> Option<PathBuf> {
+    let url = Url::parse(uri.as_str()).ok()?;
+    if url.scheme() != "file" {
+        return None
+    }
+    url.to_file_path().ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr as _;
+
+
+    /// Check that columns are reported in terms of UTF-16 code units.
+    #[test]
+    fn utf16_position_conversion() {
+        let text = "int x;\n/* ü 😀 */ bpf_probe_read(a, b, c);\n";
+        let start = text.find("bpf_probe_read").unwrap();
+        let range = Range {
+            bytes: start..start + 14,
+            start_point: Point { row: 1, col: 14 },
+            end_point: Point { row: 1, col: 28 },
+        };
+        let range = super::range(text, &range);
+        // `ü` is one UTF-16 code unit, the emoji is two.
+        assert_eq!(
+            range,
+            LspRange {
+                start: Position {
+                    line: 1,
+                    character: 11,
+                },
+                end: Position {
+                    line: 1,
+                    character: 25,
+                },
+            }
+        );
+    }
+
+    /// Check that LSP positions are converted into byte offsets
+    /// properly.
+    #[test]
+    fn offset_conversion() {
+        let text = "int x;\n/* ü 😀 */ bpf_probe_read(a, b, c);\n";
+        let start = text.find("bpf_probe_read").unwrap();
+        assert_eq!(offset(text, &Position::new(1, 11)), start);
+        assert_eq!(offset(text, &Position::new(0, 0)), 0);
+        assert_eq!(offset(text, &Position::new(0, 42)), 6);
+        assert_eq!(offset(text, &Position::new(2, 0)), text.len());
+        assert_eq!(offset(text, &Position::new(5, 3)), text.len());
+    }
+
+    /// Check that we detect overlapping ranges correctly.
+    #[test]
+    fn range_overlap() {
+        let range = |l1, c1, l2, c2| LspRange {
+            start: Position {
+                line: l1,
+                character: c1,
+            },
+            end: Position {
+                line: l2,
+                character: c2,
+            },
+        };
+
+        assert!(overlaps(&range(0, 0, 0, 5), &range(0, 3, 0, 3)));
+        assert!(overlaps(&range(0, 0, 2, 0), &range(1, 0, 1, 4)));
+        assert!(overlaps(&range(0, 0, 0, 5), &range(0, 5, 0, 7)));
+        assert!(!overlaps(&range(0, 0, 0, 5), &range(0, 6, 0, 7)));
+        assert!(!overlaps(&range(1, 0, 1, 5), &range(0, 0, 0, 7)));
+    }
+
+    /// Check that we can convert file URIs into paths.
+    #[test]
+    fn uri_path_conversion() {
+        let uri = Uri::from_str("file:///tmp/foo%20bar.bpf.c").unwrap();
+        assert_eq!(file_path(&uri), Some(PathBuf::from("/tmp/foo bar.bpf.c")));
+
+        let uri = Uri::from_str("untitled:Untitled-1").unwrap();
+        assert_eq!(file_path(&uri), None);
+    }
+}
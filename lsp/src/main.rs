@@ -0,0 +1,19 @@
+//! A Language Server Protocol server for BPF C code.
+
+mod convert;
+mod server;
+
+use anyhow::Context as _;
+use anyhow::Result;
+
+use lsp_server::Connection;
+
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let () = server::run(connection)?;
+    let () = io_threads
+        .join()
+        .context("failed to shut down I/O threads")?;
+    Ok(())
+}
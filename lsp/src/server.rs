@@ -0,0 +1,746 @@
+//! The language server's main loop and request handling.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use anyhow::Result;
+
+use lsp_server::Connection;
+use lsp_server::ErrorCode;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+
+use lsp_types::CodeAction;
+use lsp_types::CodeActionKind;
+use lsp_types::CodeActionOptions;
+use lsp_types::CodeActionOrCommand;
+use lsp_types::CodeActionParams;
+use lsp_types::CodeActionProviderCapability;
+use lsp_types::LogMessageParams;
+use lsp_types::MessageType;
+use lsp_types::Position;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::Range as LspRange;
+use lsp_types::ServerCapabilities;
+use lsp_types::ShowMessageParams;
+use lsp_types::TextDocumentSyncCapability;
+use lsp_types::TextDocumentSyncKind;
+use lsp_types::TextDocumentSyncOptions;
+use lsp_types::TextDocumentSyncSaveOptions;
+use lsp_types::TextEdit;
+use lsp_types::Uri;
+use lsp_types::WorkspaceEdit;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidChangeWatchedFiles;
+use lsp_types::notification::DidCloseTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::DidSaveTextDocument;
+use lsp_types::notification::LogMessage;
+use lsp_types::notification::Notification as NotificationTrait;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::notification::ShowMessage;
+use lsp_types::request::CodeActionRequest;
+use lsp_types::request::Request as _;
+
+use bpflint::Document as LintedDocument;
+use bpflint::Edit;
+use bpflint::LintMatch;
+use bpflint::LintOpts;
+use bpflint::Linter;
+use bpflint::builtin_lints;
+use bpflint::config::CONFIG_FILE_NAME;
+use bpflint::config::Linters;
+use bpflint::config::Selection;
+
+use crate::convert;
+
+
+/// The capabilities supported by the server.
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
+        )),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+
+/// Create the code actions available for `match` inside of `text`.
+fn code_actions(uri: &Uri, text: &str, r#match: &LintMatch) -> Vec<CodeActionOrCommand> {
+    let diagnostic = convert::diagnostic(text, r#match);
+    let action = |title: String, edit: TextEdit, is_preferred: bool| {
+        let edit = WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+            ..Default::default()
+        };
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(edit),
+            is_preferred: Some(is_preferred),
+            ..Default::default()
+        })
+    };
+
+    let mut actions = Vec::new();
+    if let Some(suggestion) = &r#match.suggestion {
+        let edit = TextEdit {
+            range: convert::range(text, &suggestion.range),
+            new_text: suggestion.replacement.clone(),
+        };
+        let title = format!("Apply suggested fix for `{}`", r#match.lint_name);
+        let () = actions.push(action(title, edit, true));
+    }
+
+    // Insert a directive on a separate line in front of the one
+    // containing the match, using the same indentation. The match may
+    // not be at the start of a statement, so disable the lint for the
+    // entire line.
+    let row = r#match.range.start_point.row;
+    let line = text.lines().nth(row).unwrap_or_default();
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let position = Position {
+        line: diagnostic.range.start.line,
+        character: 0,
+    };
+    let edit = TextEdit {
+        range: LspRange {
+            start: position,
+            end: position,
+        },
+        new_text: format!(
+            "{indent}/* bpflint: disable-next-line={} */\n",
+            r#match.lint_name
+        ),
+    };
+    let title = format!("Disable `{}` here", r#match.lint_name);
+    let () = actions.push(action(title, edit, false));
+    actions
+}
+
+
+/// Retrieve the linter to use for the document identified by `uri`.
+fn linter<'l>(linters: &'l mut Linters, default: &'l Linter, uri: &Uri) -> Result<&'l Linter> {
+    if let Some(path) = convert::file_path(uri) {
+        linters.get(&path).map(Arc::as_ref)
+    } else {
+        Ok(default)
+    }
+}
+
+
+/// An open text document.
+#[derive(Debug)]
+struct Document {
+    /// The document's current content.
+    text: String,
+    /// The document's version, as provided by the client.
+    version: i32,
+    /// The linted content, if linting succeeded.
+    linted: Option<LintedDocument>,
+}
+
+impl Document {
+    /// Retrieve the lint matches found in the current content.
+    fn matches(&self) -> &[LintMatch] {
+        self.linted
+            .as_ref()
+            .map(LintedDocument::matches)
+            .unwrap_or_default()
+    }
+}
+
+
+/// A language server publishing lint matches as diagnostics.
+struct Server {
+    /// The connection to the client.
+    connection: Connection,
+    /// Linters for documents backed by files, honoring configuration
+    /// files.
+    linters: Linters,
+    /// The linter to use for documents not backed by a file.
+    default_linter: Linter,
+    /// All currently open documents.
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server {
+    fn new(connection: Connection) -> Result<Self> {
+        let slf = Self {
+            connection,
+            linters: Linters::new(&[], Selection::default(), LintOpts::default())?,
+            default_linter: Linter::new(builtin_lints(), LintOpts::default())?,
+            documents: HashMap::new(),
+        };
+        Ok(slf)
+    }
+
+    fn send(&self, message: impl Into<Message>) -> Result<()> {
+        self.connection
+            .sender
+            .send(message.into())
+            .context("failed to send message to client")
+    }
+
+    fn show_error(&self, message: String) -> Result<()> {
+        let params = ShowMessageParams {
+            typ: MessageType::ERROR,
+            message,
+        };
+        self.send(Notification::new(ShowMessage::METHOD.to_string(), params))
+    }
+
+    fn log_error(&self, message: String) -> Result<()> {
+        let params = LogMessageParams {
+            typ: MessageType::ERROR,
+            message,
+        };
+        self.send(Notification::new(LogMessage::METHOD.to_string(), params))
+    }
+
+    /// Extract the parameters of `notification`, logging an error and
+    /// returning `None` if they are malformed.
+    fn extract<N>(&self, notification: Notification) -> Result<Option<N::Params>>
+    where
+        N: NotificationTrait,
+    {
+        match notification.extract::<N::Params>(N::METHOD) {
+            Ok(params) => Ok(Some(params)),
+            Err(err) => {
+                let message = format!("failed to parse `{}` notification: {err}", N::METHOD);
+                let () = self.log_error(message)?;
+                Ok(None)
+            },
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &Uri) -> Result<()> {
+        let (diagnostics, version) = if let Some(document) = self.documents.get(uri) {
+            let diagnostics = document
+                .matches()
+                .iter()
+                .map(|m| convert::diagnostic(&document.text, m))
+                .collect();
+            (diagnostics, Some(document.version))
+        } else {
+            (Vec::new(), None)
+        };
+
+        let params = PublishDiagnosticsParams {
+            uri: uri.clone(),
+            diagnostics,
+            version,
+        };
+        self.send(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            params,
+        ))
+    }
+
+    /// Lint the document identified by `uri` and publish the results.
+    ///
+    /// If `edits` describing the changes since the document was last
+    /// linted are provided, linting happens incrementally.
+    fn lint(&mut self, uri: &Uri, edits: Option<&[Edit]>) -> Result<()> {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return Ok(())
+        };
+
+        let result = linter(&mut self.linters, &self.default_linter, uri)
+            .and_then(|linter| match (&mut document.linted, edits) {
+                (Some(linted), Some(edits)) => linted.edit(linter, edits).map(|_matches| ()),
+                _ => {
+                    let code = document.text.clone().into_bytes();
+                    document.linted = Some(LintedDocument::new(linter, code)?);
+                    Ok(())
+                },
+            })
+            .with_context(|| format!("failed to lint `{}`", uri.as_str()));
+
+        if let Err(err) = result {
+            document.linted = None;
+            let () = self.show_error(format!("{err:#}"))?;
+        }
+        self.publish_diagnostics(uri)
+    }
+
+    /// Discard cached configuration and lint all open documents again.
+    fn reload(&mut self) -> Result<()> {
+        let () = self.linters.clear();
+        let uris = self.documents.keys().cloned().collect::<Vec<_>>();
+        for uri in uris {
+            let () = self.lint(&uri, None)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve the code actions for matches overlapping the range
+    /// provided in `params`.
+    fn code_actions(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = &params.text_document.uri;
+        self.documents
+            .get(uri)
+            .map(|document| {
+                document
+                    .matches()
+                    .iter()
+                    .filter(|m| {
+                        let range = convert::range(&document.text, &m.range);
+                        convert::overlaps(&range, &params.range)
+                    })
+                    .flat_map(|m| code_actions(uri, &document.text, m))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        let response = match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                let id = request.id.clone();
+                match request.extract::<CodeActionParams>(CodeActionRequest::METHOD) {
+                    Ok((id, params)) => Response::new_ok(id, self.code_actions(&params)),
+                    Err(err) => Response::new_err(
+                        id,
+                        ErrorCode::InvalidParams as i32,
+                        format!("failed to parse code action request: {err}"),
+                    ),
+                }
+            },
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("method `{}` is not supported", request.method),
+            ),
+        };
+        self.send(response)
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = self.extract::<DidOpenTextDocument>(notification)? else {
+                    return Ok(())
+                };
+                let document = Document {
+                    text: params.text_document.text,
+                    version: params.text_document.version,
+                    linted: None,
+                };
+                let uri = params.text_document.uri;
+                let _prev = self.documents.insert(uri.clone(), document);
+                self.lint(&uri, None)
+            },
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = self.extract::<DidChangeTextDocument>(notification)? else {
+                    return Ok(())
+                };
+                let uri = params.text_document.uri;
+                let Some(document) = self.documents.get_mut(&uri) else {
+                    return Ok(())
+                };
+
+                let mut edits = Some(Vec::new());
+                for change in params.content_changes {
+                    if let Some(range) = change.range {
+                        let start = convert::offset(&document.text, &range.start);
+                        let end = convert::offset(&document.text, &range.end).max(start);
+                        let () = document.text.replace_range(start..end, &change.text);
+                        if let Some(edits) = &mut edits {
+                            let edit = Edit {
+                                bytes: start..end,
+                                text: change.text,
+                            };
+                            let () = edits.push(edit);
+                        }
+                    } else {
+                        // The entire content got replaced; there is
+                        // no point in linting incrementally.
+                        document.text = change.text;
+                        edits = None;
+                    }
+                }
+                document.version = params.text_document.version;
+                self.lint(&uri, edits.as_deref())
+            },
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = self.extract::<DidCloseTextDocument>(notification)? else {
+                    return Ok(())
+                };
+                let uri = params.text_document.uri;
+                let _document = self.documents.remove(&uri);
+                self.publish_diagnostics(&uri)
+            },
+            DidSaveTextDocument::METHOD => {
+                let Some(params) = self.extract::<DidSaveTextDocument>(notification)? else {
+                    return Ok(())
+                };
+                if is_config_file(&params.text_document.uri) {
+                    self.reload()
+                } else {
+                    Ok(())
+                }
+            },
+            DidChangeWatchedFiles::METHOD => {
+                let Some(params) = self.extract::<DidChangeWatchedFiles>(notification)? else {
+                    return Ok(())
+                };
+                if params
+                    .changes
+                    .iter()
+                    .any(|event| is_config_file(&event.uri))
+                {
+                    self.reload()
+                } else {
+                    Ok(())
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let receiver = self.connection.receiver.clone();
+        for message in &receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(())
+                    }
+                    let () = self.handle_request(request)?;
+                },
+                Message::Notification(notification) => {
+                    let () = self.handle_notification(notification)?;
+                },
+                Message::Response(_response) => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Check whether `uri` refers to a bpflint configuration file.
+fn is_config_file(uri: &Uri) -> bool {
+    convert::file_path(uri).is_some_and(|path| {
+        path.file_name()
+            .is_some_and(|name| name == CONFIG_FILE_NAME)
+    })
+}
+
+
+/// Serve the client on the other end of `connection` until it requests
+/// shutdown.
+pub fn run(connection: Connection) -> Result<()> {
+    let capabilities =
+        serde_json::to_value(capabilities()).context("failed to serialize server capabilities")?;
+    let _params = connection
+        .initialize(capabilities)
+        .context("failed to initialize connection")?;
+
+    let mut server = Server::new(connection)?;
+    server.run()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr as _;
+    use std::thread;
+
+    use lsp_server::RequestId;
+
+    use lsp_types::CodeActionContext;
+    use lsp_types::DiagnosticSeverity;
+    use lsp_types::DidChangeTextDocumentParams;
+    use lsp_types::DidCloseTextDocumentParams;
+    use lsp_types::DidOpenTextDocumentParams;
+    use lsp_types::InitializeParams;
+    use lsp_types::NumberOrString;
+    use lsp_types::TextDocumentContentChangeEvent;
+    use lsp_types::TextDocumentIdentifier;
+    use lsp_types::TextDocumentItem;
+    use lsp_types::VersionedTextDocumentIdentifier;
+    use lsp_types::notification::Exit;
+    use lsp_types::notification::Initialized;
+    use lsp_types::request::Initialize;
+    use lsp_types::request::Shutdown;
+
+    use serde::Serialize;
+
+    use serde_json::Value;
+
+
+    /// A minimal client for testing purposes.
+    struct Client {
+        connection: Connection,
+    }
+
+    impl Client {
+        fn request<P: Serialize>(&self, id: i32, method: &str, params: P) -> Value {
+            self.request_response(id, method, params)
+                .response_result
+                .unwrap()
+        }
+
+        fn request_response<P: Serialize>(&self, id: i32, method: &str, params: P) -> Response {
+            let request = Request::new(RequestId::from(id), method.to_string(), params);
+            let () = self.connection.sender.send(request.into()).unwrap();
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) => {
+                    assert_eq!(response.id, RequestId::from(id));
+                    response
+                },
+                message => panic!("received unexpected message: {message:?}"),
+            }
+        }
+
+        fn notify<P: Serialize>(&self, method: &str, params: P) {
+            let notification = Notification::new(method.to_string(), params);
+            let () = self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        fn recv_notification(&self) -> Notification {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Notification(notification) => notification,
+                message => panic!("received unexpected message: {message:?}"),
+            }
+        }
+    }
+
+
+    /// Check that we publish diagnostics for opened documents and
+    /// offer quick-fixes for them.
+    #[test]
+    fn diagnostics_and_code_actions() {
+        let (server, client) = Connection::memory();
+        let server = thread::spawn(move || run(server));
+        let client = Client { connection: client };
+
+        let _result = client.request(1, Initialize::METHOD, InitializeParams::default());
+        let () = client.notify(Initialized::METHOD, serde_json::json!({}));
+
+        let uri = Uri::from_str("untitled:test.bpf.c").unwrap();
+        let text = concat!(
+            "struct {\n    __uint(key_size, sizeof(u32));\n} map;\n",
+            "int f(void) {\n    bpf_probe_read(a, b, c);\n}\n",
+        );
+        let params = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "c".to_string(),
+                version: 1,
+                text: text.to_string(),
+            },
+        };
+        let () = client.notify(DidOpenTextDocument::METHOD, params);
+
+        let notification = client.recv_notification();
+        assert_eq!(notification.method, PublishDiagnostics::METHOD);
+        let params =
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap();
+        assert_eq!(params.version, Some(1));
+        assert_eq!(params.diagnostics.len(), 2);
+        let diagnostic = &params.diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("untyped-map-member".to_string()))
+        );
+        let diagnostic = &params.diagnostics[1];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("probe-read".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.range.start, Position::new(4, 4));
+        assert_eq!(diagnostic.range.end, Position::new(4, 18));
+
+        let code_actions = |id, position| {
+            let params = CodeActionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                range: LspRange::new(position, position),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            };
+            let result = client.request(id, CodeActionRequest::METHOD, params);
+            serde_json::from_value::<Vec<CodeAction>>(result).unwrap()
+        };
+
+        let actions = code_actions(2, Position::new(1, 6));
+        assert_eq!(actions.len(), 2);
+        assert_eq!(
+            actions[0].title,
+            "Apply suggested fix for `untyped-map-member`"
+        );
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits,
+            &[TextEdit {
+                range: LspRange::new(Position::new(1, 4), Position::new(1, 33)),
+                new_text: "__type(key, u32)".to_string(),
+            }]
+        );
+        assert_eq!(actions[1].title, "Disable `untyped-map-member` here");
+
+        // `probe-read` matches come without a suggested fix.
+        let actions = code_actions(3, Position::new(4, 6));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Disable `probe-read` here");
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits,
+            &[TextEdit {
+                range: LspRange::new(Position::new(4, 0), Position::new(4, 0)),
+                new_text: "    /* bpflint: disable-next-line=probe-read */\n".to_string(),
+            }]
+        );
+
+        let params = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(LspRange::new(Position::new(4, 18), Position::new(4, 18))),
+                range_length: None,
+                text: "_kernel".to_string(),
+            }],
+        };
+        let () = client.notify(DidChangeTextDocument::METHOD, params);
+        let notification = client.recv_notification();
+        let params =
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap();
+        assert_eq!(params.version, Some(2));
+        assert_eq!(params.diagnostics.len(), 1);
+
+        let params = DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri },
+        };
+        let () = client.notify(DidCloseTextDocument::METHOD, params);
+        let notification = client.recv_notification();
+        let params =
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap();
+        assert!(params.diagnostics.is_empty());
+
+        let _result = client.request(4, Shutdown::METHOD, ());
+        let () = client.notify(Exit::METHOD, ());
+        let () = server.join().unwrap().unwrap();
+    }
+
+
+    /// Make sure that malformed notifications and requests are reported
+    /// without bringing down the server.
+    #[test]
+    fn malformed_params() {
+        let (server, client) = Connection::memory();
+        let server = thread::spawn(move || run(server));
+        let client = Client { connection: client };
+
+        let _result = client.request(1, Initialize::METHOD, InitializeParams::default());
+        let () = client.notify(Initialized::METHOD, serde_json::json!({}));
+
+        let () = client.notify(DidOpenTextDocument::METHOD, serde_json::json!({}));
+        let notification = client.recv_notification();
+        assert_eq!(notification.method, LogMessage::METHOD);
+        let params = serde_json::from_value::<LogMessageParams>(notification.params).unwrap();
+        assert_eq!(params.typ, MessageType::ERROR);
+        assert!(
+            params.message.contains(DidOpenTextDocument::METHOD),
+            "{}",
+            params.message
+        );
+
+        let response = client.request_response(2, CodeActionRequest::METHOD, serde_json::json!({}));
+        let error = response.response_result.unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+
+        let _result = client.request(3, Shutdown::METHOD, ());
+        let () = client.notify(Exit::METHOD, ());
+        let () = server.join().unwrap().unwrap();
+    }
+
+
+    /// Check that the "disable" quick-fix suppresses a match that is not
+    /// the first statement on its line.
+    #[test]
+    fn disable_action() {
+        let (server, client) = Connection::memory();
+        let server = thread::spawn(move || run(server));
+        let client = Client { connection: client };
+
+        let _result = client.request(1, Initialize::METHOD, InitializeParams::default());
+        let () = client.notify(Initialized::METHOD, serde_json::json!({}));
+
+        let uri = Uri::from_str("untitled:test.bpf.c").unwrap();
+        let text = "int f(void) {\n    int x = 0; bpf_probe_read(a, b, c);\n}\n";
+        let params = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "c".to_string(),
+                version: 1,
+                text: text.to_string(),
+            },
+        };
+        let () = client.notify(DidOpenTextDocument::METHOD, params);
+
+        let notification = client.recv_notification();
+        let params =
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range.start, Position::new(1, 15));
+
+        let position = Position::new(1, 16);
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range: LspRange::new(position, position),
+            context: CodeActionContext::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let result = client.request(2, CodeActionRequest::METHOD, params);
+        let actions = serde_json::from_value::<Vec<CodeAction>>(result).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Disable `probe-read` here");
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits.len(), 1);
+
+        let params = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(edits[0].range),
+                range_length: None,
+                text: edits[0].new_text.clone(),
+            }],
+        };
+        let () = client.notify(DidChangeTextDocument::METHOD, params);
+        let notification = client.recv_notification();
+        let params =
+            serde_json::from_value::<PublishDiagnosticsParams>(notification.params).unwrap();
+        assert_eq!(params.version, Some(2));
+        assert!(params.diagnostics.is_empty(), "{:?}", params.diagnostics);
+
+        let _result = client.request(3, Shutdown::METHOD, ());
+        let () = client.notify(Exit::METHOD, ());
+        let () = server.join().unwrap().unwrap();
+    }
+}
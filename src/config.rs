@@ -0,0 +1,764 @@
+//! Support for `bpflint.toml` configuration files.
+//!
+//! Configuration files are discovered by walking up the directory
+//! hierarchy starting at the source file being linted.
+//!
+//! A configuration file may look as follows:
+//! ```toml
+//! # The minimum kernel version being targeted.
+//! kernel_version = "5.15.0"
+//! # Additional directories containing lints (`*.scm` & `*.txt` pairs),
+//! # relative to the configuration file.
+//! lint_dirs = ["lints/"]
+//! # Lints to disable and enable, respectively. `all` refers to all
+//! # available lints.
+//! disable = ["probe-read"]
+//! enable = []
+//! # Lints to report at the respective level.
+//! allow = []
+//! warn = []
+//! deny = ["core-read"]
+//!
+//! # Settings applying only to files matching any of the provided
+//! # globs, relative to the configuration file.
+//! [[overrides]]
+//! paths = ["tests/**/*.bpf.c"]
+//! disable = ["unstable-attach-point"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::path::PathBuf;
+use std::path::absolute;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::bail;
+
+use globset::GlobBuilder;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+
+use serde::Deserialize;
+
+use crate::Level;
+use crate::Lint;
+use crate::LintOpts;
+use crate::Linter;
+use crate::Version;
+use crate::builtin_lints;
+use crate::lint::SYNTHETIC_LINTS;
+use crate::load_lints_from_dir;
+
+
+/// The name of the configuration file we search for.
+pub const CONFIG_FILE_NAME: &str = "bpflint.toml";
+/// The name referring to all available lints.
+const ALL_LINTS: &str = "all";
+
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawOverride {
+    paths: Vec<String>,
+    kernel_version: Option<String>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+    allow: Vec<String>,
+    warn: Vec<String>,
+    deny: Vec<String>,
+}
+
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    kernel_version: Option<String>,
+    lint_dirs: Vec<PathBuf>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+    allow: Vec<String>,
+    warn: Vec<String>,
+    deny: Vec<String>,
+    overrides: Vec<RawOverride>,
+}
+
+
+fn parse_kernel_version(version: Option<String>) -> Result<Option<Version>> {
+    version
+        .map(|version| {
+            version
+                .parse::<Version>()
+                .context("encountered invalid `kernel_version` setting")
+        })
+        .transpose()
+}
+
+
+/// A selection of lints to disable, (re-)enable, and to report at
+/// certain levels.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Lints to disable.
+    pub disable: Vec<String>,
+    /// Lints to enable (at the warning level), if disabled.
+    pub enable: Vec<String>,
+    /// Lints to allow, i.e., not to report.
+    pub allow: Vec<String>,
+    /// Lints to report as warnings.
+    pub warn: Vec<String>,
+    /// Lints to report as errors.
+    pub deny: Vec<String>,
+    /// The names of lints reported by the application itself (e.g.,
+    /// based on the file name), which may be selected in addition to
+    /// the available lints and those reported by the linter (e.g.,
+    /// `parse-error`).
+    pub synthetic: Vec<String>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Selection {
+    /// Apply the selection to the lint `levels`. Lints are first
+    /// disabled, then enabled, and then set to the allow, warn, and
+    /// deny levels, in this order.
+    pub fn apply(&self, levels: &mut BTreeMap<String, Level>, available: &[Lint]) -> Result<()> {
+        self.apply_impl(levels, available, &self.synthetic)
+    }
+
+    /// Apply the selection to the lint `levels`, accepting the
+    /// application reported lints in `synthetic`.
+    fn apply_impl(
+        &self,
+        levels: &mut BTreeMap<String, Level>,
+        available: &[Lint],
+        synthetic: &[String],
+    ) -> Result<()> {
+        let Self {
+            disable,
+            enable,
+            allow,
+            warn,
+            deny,
+            synthetic: _,
+            _non_exhaustive: (),
+        } = self;
+
+        let find = |name: &String| -> Result<Vec<String>> {
+            if name == ALL_LINTS {
+                Ok(available.iter().map(|lint| lint.name.clone()).collect())
+            } else if available.iter().any(|lint| &lint.name == name)
+                || SYNTHETIC_LINTS.contains(&name.as_str())
+                || synthetic.contains(name)
+            {
+                Ok(vec![name.clone()])
+            } else {
+                bail!("encountered unknown lint `{name}`")
+            }
+        };
+
+        for name in disable {
+            for name in find(name)? {
+                let _prev = levels.insert(name, Level::Allow);
+            }
+        }
+        for name in enable {
+            for name in find(name)? {
+                let level = levels.entry(name).or_default();
+                if *level == Level::Allow {
+                    *level = Level::Warn;
+                }
+            }
+        }
+
+        for (names, level) in [
+            (allow, Level::Allow),
+            (warn, Level::Warn),
+            (deny, Level::Deny),
+        ] {
+            for name in names {
+                for name in find(name)? {
+                    let _prev = levels.insert(name, level);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Settings applying to a subset of files.
+#[derive(Debug)]
+struct Override {
+    globs: GlobSet,
+    kernel_version: Option<Version>,
+    selection: Selection,
+}
+
+impl Override {
+    fn new(raw: RawOverride) -> Result<Self> {
+        let RawOverride {
+            paths,
+            kernel_version,
+            enable,
+            disable,
+            allow,
+            warn,
+            deny,
+        } = raw;
+
+        let mut builder = GlobSetBuilder::new();
+        for path in paths {
+            let glob = GlobBuilder::new(&path)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("encountered invalid path glob `{path}`"))?;
+            let _builder = builder.add(glob);
+        }
+
+        let slf = Self {
+            globs: builder.build()?,
+            kernel_version: parse_kernel_version(kernel_version)?,
+            selection: Selection {
+                disable,
+                enable,
+                allow,
+                warn,
+                deny,
+                synthetic: Vec::new(),
+                _non_exhaustive: (),
+            },
+        };
+        Ok(slf)
+    }
+}
+
+
+/// The effective settings for linting a single file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Settings {
+    /// The levels of all available lints, keyed by name.
+    pub levels: BTreeMap<String, Level>,
+    /// The minimum kernel version being targeted.
+    pub kernel_version: Option<Version>,
+}
+
+impl Settings {
+    /// Create the default settings in the absence of a configuration
+    /// file: all available lints reported at their default level
+    /// (i.e., as warnings, except for lints only checked on request)
+    /// and no kernel version set.
+    pub fn new(available: &[Lint]) -> Self {
+        Self {
+            levels: available
+                .iter()
+                .map(|lint| (lint.name.clone(), Level::default_for(&lint.name)))
+                .collect(),
+            kernel_version: None,
+        }
+    }
+}
+
+
+/// A parsed `bpflint.toml` configuration file.
+#[derive(Debug)]
+pub struct Config {
+    /// The absolute path to the configuration file.
+    path: PathBuf,
+    /// The directory containing the configuration file.
+    dir: PathBuf,
+    kernel_version: Option<Version>,
+    /// Directories containing additional lints, as absolute paths.
+    lint_dirs: Vec<PathBuf>,
+    selection: Selection,
+    overrides: Vec<Override>,
+}
+
+impl Config {
+    /// Search for a configuration file applying to the source file at
+    /// `src_path`, starting at its directory and walking up towards
+    /// the file system root.
+    pub fn discover(src_path: &Path) -> Result<Option<PathBuf>> {
+        let src_path = absolute(src_path)
+            .with_context(|| format!("failed to make `{}` absolute", src_path.display()))?;
+        for dir in src_path.ancestors().skip(1) {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path
+                .try_exists()
+                .with_context(|| format!("failed to check existence of `{}`", path.display()))?
+            {
+                return Ok(Some(path))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let path = absolute(path)
+            .with_context(|| format!("failed to make `{}` absolute", path.display()))?;
+        let content = read_to_string(&path)
+            .with_context(|| format!("failed to read configuration `{}`", path.display()))?;
+        Self::parse(&content, &path)
+            .with_context(|| format!("failed to parse configuration `{}`", path.display()))
+    }
+
+    fn parse(content: &str, path: &Path) -> Result<Self> {
+        let RawConfig {
+            kernel_version,
+            lint_dirs,
+            enable,
+            disable,
+            allow,
+            warn,
+            deny,
+            overrides,
+        } = toml::from_str::<RawConfig>(content)?;
+
+        // SANITY: `path` is absolute and refers to a file and so it
+        //         will always have a parent.
+        let dir = path.parent().unwrap().to_path_buf();
+        let slf = Self {
+            kernel_version: parse_kernel_version(kernel_version)?,
+            lint_dirs: lint_dirs
+                .into_iter()
+                .map(|lint_dir| dir.join(lint_dir))
+                .collect(),
+            selection: Selection {
+                disable,
+                enable,
+                allow,
+                warn,
+                deny,
+                synthetic: Vec::new(),
+                _non_exhaustive: (),
+            },
+            overrides: overrides
+                .into_iter()
+                .map(Override::new)
+                .collect::<Result<_>>()?,
+            path: path.to_path_buf(),
+            dir,
+        };
+        Ok(slf)
+    }
+
+    /// Retrieve the path to the configuration file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the additional lints from the configured lint directories.
+    pub fn extra_lints(&self) -> Result<Vec<Lint>> {
+        let mut lints = Vec::new();
+        for lint_dir in &self.lint_dirs {
+            let () = lints.extend(load_lints_from_dir(lint_dir)?);
+        }
+        Ok(lints)
+    }
+
+    /// Determine the effective settings for the source file at
+    /// `src_path`, given the set of `available` lints and the names of
+    /// lints reported by the application itself (see
+    /// [`Selection::synthetic`]).
+    pub fn resolve(
+        &self,
+        src_path: &Path,
+        available: &[Lint],
+        synthetic: &[String],
+    ) -> Result<Settings> {
+        let mut settings = Settings::new(available);
+        settings.kernel_version = self.kernel_version;
+        let () = self
+            .selection
+            .apply_impl(&mut settings.levels, available, synthetic)?;
+
+        let src_path = absolute(src_path)
+            .with_context(|| format!("failed to make `{}` absolute", src_path.display()))?;
+        let Ok(rel_path) = src_path.strip_prefix(&self.dir) else {
+            return Ok(settings)
+        };
+
+        for r#override in &self.overrides {
+            if r#override.globs.is_match(rel_path) {
+                if let Some(kernel_version) = r#override.kernel_version {
+                    settings.kernel_version = Some(kernel_version);
+                }
+                let () =
+                    r#override
+                        .selection
+                        .apply_impl(&mut settings.levels, available, synthetic)?;
+            }
+        }
+        Ok(settings)
+    }
+}
+
+
+/// Check that the provided lints have unique names.
+fn check_lint_names(lints: &[Lint]) -> Result<()> {
+    let mut names = HashSet::new();
+    for lint in lints {
+        if !names.insert(&lint.name) {
+            bail!("encountered multiple lints named `{}`", lint.name)
+        }
+    }
+    Ok(())
+}
+
+
+/// A cache of [`Linter`]s for the various configurations in use.
+///
+/// For each source file, the applicable configuration file is
+/// discovered and the effective settings are determined, with settings
+/// provided at construction time taking precedence.
+#[derive(Debug)]
+pub struct Linters {
+    /// The built-in lints along with those loaded from explicitly
+    /// provided directories.
+    lints: Vec<Lint>,
+    /// Loaded configurations along with the lints available to them,
+    /// keyed by configuration file path.
+    configs: HashMap<PathBuf, (Config, Vec<Lint>)>,
+    /// Linters keyed by configuration file path and effective settings.
+    linters: HashMap<(Option<PathBuf>, Settings), Arc<Linter>>,
+    /// The lint selection taking precedence over anything configured.
+    selection: Selection,
+    /// The options to base all linters on.
+    opts: LintOpts,
+}
+
+impl Linters {
+    /// Create a new [`Linters`] object using the built-in lints as
+    /// well as those contained in `lint_dirs`.
+    ///
+    /// `opts` serve as the basis for all linters. The kernel version
+    /// contained in it, if any, as well as `selection` take precedence
+    /// over what is configured in configuration files. Lint levels
+    /// contained in `opts` are ignored in favor of `selection`, whose
+    /// synthetic lints are known to all linters.
+    pub fn new(lint_dirs: &[PathBuf], selection: Selection, mut opts: LintOpts) -> Result<Self> {
+        let () = opts
+            .synthetic_lints
+            .extend(selection.synthetic.iter().cloned());
+
+        let mut lints = builtin_lints().collect::<Vec<_>>();
+        for lint_dir in lint_dirs {
+            let () = lints.extend(load_lints_from_dir(lint_dir)?);
+        }
+        let () = check_lint_names(&lints)?;
+
+        let slf = Self {
+            lints,
+            configs: HashMap::new(),
+            linters: HashMap::new(),
+            selection,
+            opts,
+        };
+        Ok(slf)
+    }
+
+    /// Load the configuration file at `config_path`, along with the
+    /// lints available to it, if not already cached.
+    fn load_config(&mut self, config_path: &Path) -> Result<&(Config, Vec<Lint>)> {
+        let entry = match self.configs.entry(config_path.to_path_buf()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let config = Config::load(config_path)?;
+                let extra = config.extra_lints().with_context(|| {
+                    format!(
+                        "failed to load lints configured in `{}`",
+                        config_path.display()
+                    )
+                })?;
+                let available = self.lints.iter().cloned().chain(extra).collect::<Vec<_>>();
+                let () = check_lint_names(&available).with_context(|| {
+                    format!(
+                        "failed to load lints configured in `{}`",
+                        config_path.display()
+                    )
+                })?;
+                entry.insert((config, available))
+            },
+        };
+        Ok(entry)
+    }
+
+    /// Retrieve the lints available for the source file at
+    /// `src_path`, i.e., the built-in lints, those loaded from
+    /// explicitly provided directories, and those loaded by the
+    /// applicable configuration file, if any.
+    pub fn available(&mut self, src_path: &Path) -> Result<&[Lint]> {
+        if let Some(config_path) = Config::discover(src_path)? {
+            let (_config, available) = self.load_config(&config_path)?;
+            Ok(available)
+        } else {
+            Ok(&self.lints)
+        }
+    }
+
+    /// Retrieve the linter to use for the source file at `src_path`.
+    ///
+    /// The linter can be cloned cheaply, e.g., for sharing it between
+    /// threads.
+    pub fn get(&mut self, src_path: &Path) -> Result<&Arc<Linter>> {
+        let config_path = Config::discover(src_path)?;
+        let (available, mut settings) = if let Some(config_path) = &config_path {
+            let _entry = self.load_config(config_path)?;
+            // SANITY: The configuration got loaded above.
+            let (config, available) = self.configs.get(config_path).unwrap();
+            let settings = config
+                .resolve(src_path, available, &self.selection.synthetic)
+                .with_context(|| {
+                    format!(
+                        "failed to apply configuration `{}` to `{}`",
+                        config.path().display(),
+                        src_path.display()
+                    )
+                })?;
+            (available.as_slice(), settings)
+        } else {
+            (self.lints.as_slice(), Settings::new(&self.lints))
+        };
+
+        if let Some(kernel_version) = self.opts.kernel_version {
+            settings.kernel_version = Some(kernel_version);
+        }
+        let () = self
+            .selection
+            .apply(&mut settings.levels, available)
+            .context("failed to apply lint selection")?;
+
+        let linter = match self.linters.entry((config_path, settings)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (_, settings) = entry.key();
+                let lint_opts = LintOpts {
+                    kernel_version: settings.kernel_version,
+                    levels: settings.levels.clone().into_iter().collect(),
+                    ..self.opts.clone()
+                };
+                let linter = Linter::new(available, lint_opts)?;
+                entry.insert(Arc::new(linter))
+            },
+        };
+        Ok(linter)
+    }
+
+    /// Discard all cached configurations and linters, e.g., because
+    /// configuration files may have changed.
+    pub fn clear(&mut self) {
+        let () = self.configs.clear();
+        let () = self.linters.clear();
+    }
+
+    /// Retrieve the lints used by any of the linters, sorted by name.
+    pub fn lints(&self) -> impl Iterator<Item = &Lint> {
+        self.linters
+            .values()
+            .flat_map(|linter| linter.lints())
+            .map(|lint| (&lint.name, lint))
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::create_dir_all;
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+
+    /// Retrieve the names of all lints that are not allowed, along
+    /// with their levels.
+    fn enabled(settings: &Settings) -> Vec<(&str, Level)> {
+        settings
+            .levels
+            .iter()
+            .filter(|(_name, level)| **level != Level::Allow)
+            .map(|(name, level)| (name.as_str(), *level))
+            .collect()
+    }
+
+    /// Check that we can parse a configuration file and resolve
+    /// settings for files based on it.
+    #[test]
+    fn resolve_settings() {
+        let available = builtin_lints().collect::<Vec<_>>();
+        let config = r#"
+            kernel_version = "5.4.0"
+            disable = ["all"]
+            enable = ["probe-read", "core-read"]
+            deny = ["probe-read"]
+
+            [[overrides]]
+            paths = ["tests/**/*.bpf.c"]
+            kernel_version = "6.1.0"
+            disable = ["probe-read"]
+
+            [[overrides]]
+            paths = ["*.bpf.c"]
+            enable = ["bpf-loop"]
+        "#;
+        let config = Config::parse(config, Path::new("/project/bpflint.toml")).unwrap();
+
+        let settings = config
+            .resolve(Path::new("/project/src/foo.bpf.c"), &available, &[])
+            .unwrap();
+        assert_eq!(settings.kernel_version, Some(Version(5, 4, 0)));
+        assert_eq!(
+            enabled(&settings),
+            [("core-read", Level::Warn), ("probe-read", Level::Deny)]
+        );
+
+        let settings = config
+            .resolve(Path::new("/project/tests/sub/foo.bpf.c"), &available, &[])
+            .unwrap();
+        assert_eq!(settings.kernel_version, Some(Version(6, 1, 0)));
+        assert_eq!(enabled(&settings), [("core-read", Level::Warn)]);
+
+        let settings = config
+            .resolve(Path::new("/project/foo.bpf.c"), &available, &[])
+            .unwrap();
+        assert_eq!(settings.kernel_version, Some(Version(5, 4, 0)));
+        assert_eq!(
+            enabled(&settings),
+            [
+                ("bpf-loop", Level::Warn),
+                ("core-read", Level::Warn),
+                ("probe-read", Level::Deny)
+            ]
+        );
+
+        // Files outside of the configuration file's directory are not
+        // subject to overrides.
+        let settings = config
+            .resolve(Path::new("/elsewhere/tests/foo.bpf.c"), &available, &[])
+            .unwrap();
+        assert_eq!(
+            enabled(&settings),
+            [("core-read", Level::Warn), ("probe-read", Level::Deny)]
+        );
+    }
+
+    /// Check that lints not backed by queries can be selected as well.
+    #[test]
+    fn synthetic_lint_selection() {
+        let available = builtin_lints().collect::<Vec<_>>();
+        let mut levels = BTreeMap::new();
+        let selection = Selection {
+            allow: vec!["bogus-file-extension".to_string()],
+            deny: vec!["parse-error".to_string(), "unknown-lint".to_string()],
+            synthetic: vec!["bogus-file-extension".to_string()],
+            ..Default::default()
+        };
+        let () = selection.apply(&mut levels, &available).unwrap();
+        assert_eq!(levels["bogus-file-extension"], Level::Allow);
+        assert_eq!(levels["parse-error"], Level::Deny);
+        assert_eq!(levels["unknown-lint"], Level::Deny);
+
+        // Application reported lints are only known once registered.
+        let selection = Selection {
+            allow: vec!["bogus-file-extension".to_string()],
+            ..Default::default()
+        };
+        let err = selection.apply(&mut levels, &available).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "encountered unknown lint `bogus-file-extension`"
+        );
+
+        let config = r#"warn = ["bogus-file-extension", "unused-directive"]"#;
+        let config = Config::parse(config, Path::new("/bpflint.toml")).unwrap();
+        let synthetic = ["bogus-file-extension".to_string()];
+        let settings = config
+            .resolve(Path::new("/foo.bpf.c"), &available, &synthetic)
+            .unwrap();
+        assert_eq!(settings.levels["bogus-file-extension"], Level::Warn);
+        assert_eq!(settings.levels["unused-directive"], Level::Warn);
+    }
+
+    /// Make sure that invalid configuration files are rejected.
+    #[test]
+    fn invalid_config() {
+        let available = builtin_lints().collect::<Vec<_>>();
+        let path = Path::new("/bpflint.toml");
+
+        let config = r#"kernel_version = "5.4""#;
+        assert!(Config::parse(config, path).is_err());
+
+        let config = r#"unknown = true"#;
+        assert!(Config::parse(config, path).is_err());
+
+        let config = r#"
+            [[overrides]]
+            paths = ["[invalid"]
+        "#;
+        assert!(Config::parse(config, path).is_err());
+
+        let config = r#"disable = ["probe_read"]"#;
+        let config = Config::parse(config, path).unwrap();
+        let err = config
+            .resolve(Path::new("/foo.bpf.c"), &available, &[])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "encountered unknown lint `probe_read`");
+    }
+
+    /// Check that configuration files are discovered in parent
+    /// directories and that lints from configured lint directories
+    /// are loaded.
+    #[test]
+    fn discover_and_load() {
+        let root = TempDir::new().unwrap();
+        let src_dir = root.path().join("src").join("nested");
+        let () = create_dir_all(&src_dir).unwrap();
+        let src_path = src_dir.join("foo.bpf.c");
+        assert_eq!(Config::discover(&src_path).unwrap(), None);
+
+        let lint_dir = root.path().join("lints");
+        let () = create_dir_all(&lint_dir).unwrap();
+        let () = write(
+            lint_dir.join("no-foo.scm"),
+            r#"(call_expression function: (identifier) @f (#eq? @f "foo"))"#,
+        )
+        .unwrap();
+        let () = write(lint_dir.join("no-foo.txt"), "don't call foo\n").unwrap();
+
+        let config_path = root.path().join(CONFIG_FILE_NAME);
+        let () = write(&config_path, r#"lint_dirs = ["lints"]"#).unwrap();
+        assert_eq!(
+            Config::discover(&src_path).unwrap().as_deref(),
+            Some(config_path.as_path())
+        );
+
+        let config = Config::load(&config_path).unwrap();
+        let lints = config.extra_lints().unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].name, "no-foo");
+        assert_eq!(lints[0].message, "don't call foo");
+
+        let mut linters = Linters::new(&[], Selection::default(), LintOpts::default()).unwrap();
+        let available = linters.available(&src_path).unwrap();
+        assert!(available.iter().any(|lint| lint.name == "no-foo"));
+        assert!(available.iter().any(|lint| lint.name == "probe-read"));
+
+        let available = linters.available(&root.path().join("foo.bpf.c")).unwrap();
+        assert!(available.iter().any(|lint| lint.name == "no-foo"));
+
+        let other = TempDir::new().unwrap();
+        let available = linters.available(&other.path().join("foo.bpf.c")).unwrap();
+        assert!(!available.iter().any(|lint| lint.name == "no-foo"));
+    }
+}
@@ -0,0 +1,593 @@
+//! Parsing and evaluation of `bpflint:` directive comments.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use tracing::warn;
+
+use tree_sitter::Node;
+use tree_sitter::Tree;
+
+use crate::Level;
+use crate::LintMatch;
+use crate::Range;
+
+
+/// The prefix identifying a comment as a directive.
+const PREFIX: &str = "bpflint:";
+
+/// The lint name referring to all lints.
+const ALL: &str = "all";
+
+
+/// The action a directive requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Action {
+    /// Disable lints for the following item.
+    Disable,
+    /// Disable lints for the line following the comment.
+    DisableNextLine,
+    /// Disable lints for the entire file.
+    DisableFile,
+    /// Enable lints for the following item, even if they were disabled
+    /// for an enclosing one.
+    Enable,
+}
+
+
+/// A parsed `bpflint:` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Directive {
+    /// The requested action.
+    pub action: Action,
+    /// The names of the lints the directive applies to.
+    pub lints: Vec<String>,
+    /// The reason given for the directive, if any.
+    pub reason: Option<String>,
+}
+
+impl Directive {
+    /// Parse a directive from the text of a comment, including its
+    /// comment syntax.
+    ///
+    /// A directive has the form `bpflint: <action>=<lint>[,<lint>...]`,
+    /// optionally followed by ` -- <reason>`.
+    ///
+    /// Returns `Ok(None)` if the comment is not a directive at all.
+    pub fn parse(comment: &str) -> Result<Option<Self>> {
+        // The comment node will still contain the actual comment
+        // syntax, unfortunately.
+        let comment = if let Some(comment) = comment.strip_prefix("//") {
+            comment
+        } else if let Some(comment) = comment.strip_prefix("/*") {
+            comment.strip_suffix("*/").unwrap_or(comment)
+        } else {
+            comment
+        };
+
+        let Some(directive) = comment.trim().strip_prefix(PREFIX) else {
+            return Ok(None)
+        };
+        let directive = directive.trim();
+        let Some((action, lints)) = directive.split_once('=') else {
+            bail!("directive `{directive}` lacks a `=<lint>[,<lint>...]` part")
+        };
+
+        let action = match action.trim() {
+            "disable" => Action::Disable,
+            "disable-next-line" => Action::DisableNextLine,
+            "disable-file" => Action::DisableFile,
+            "enable" => Action::Enable,
+            action => bail!("directive `{directive}` has unknown action `{action}`"),
+        };
+
+        let (lints, reason) = match lints.split_once("--") {
+            Some((lints, reason)) => {
+                let reason = reason.trim();
+                (lints, (!reason.is_empty()).then(|| reason.to_string()))
+            },
+            None => (lints, None),
+        };
+
+        let lints = lints
+            .split(',')
+            .map(|lint| {
+                let lint = lint.trim();
+                if lint.is_empty() {
+                    bail!("directive `{directive}` contains an empty lint name")
+                }
+                Ok(lint.to_string())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let slf = Self {
+            action,
+            lints,
+            reason,
+        };
+        Ok(Some(slf))
+    }
+
+    /// Check whether the directive applies to the lint with the given
+    /// name, returning the index of the matching entry in `lints`.
+    pub fn applies_to(&self, lint_name: &str) -> Option<usize> {
+        self.lints
+            .iter()
+            .position(|lint| lint == ALL || lint == lint_name)
+    }
+}
+
+
+/// Parse the directive contained in the comment `node`, if any.
+fn parse_comment(node: Node<'_>, code: &[u8]) -> Result<Option<Directive>> {
+    let comment = &code[node.byte_range()];
+    let Ok(comment) = str::from_utf8(comment) else {
+        // If it's not valid UTF-8 it can't be a comment for us to
+        // consider.
+        warn!(
+            "encountered invalid UTF-8 in code comment at bytes `{}..{}`",
+            node.start_byte(),
+            node.end_byte()
+        );
+        return Ok(None)
+    };
+
+    Directive::parse(comment)
+}
+
+
+/// The name under which directives that never suppressed a match are
+/// reported.
+pub(crate) const UNUSED_DIRECTIVE: &str = "unused-directive";
+
+/// The name under which directives referring to unknown lints are
+/// reported.
+pub(crate) const UNKNOWN_LINT: &str = "unknown-lint";
+
+/// The name under which directives lacking a reason are reported.
+pub(crate) const UNJUSTIFIED_DIRECTIVE: &str = "unjustified-directive";
+
+/// The name under which malformed directives are reported.
+pub(crate) const INVALID_DIRECTIVE: &str = "invalid-directive";
+
+
+/// The part of the code a [`Suppression`] applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// The item (statement, block, function, ...) directly following
+    /// the directive (`bpflint: disable=...`).
+    Item,
+    /// The line following the directive (`bpflint:
+    /// disable-next-line=...`).
+    NextLine,
+    /// The entire file (`bpflint: disable-file=...`).
+    File,
+}
+
+
+/// A suppression of lints by means of a `bpflint:` directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suppression {
+    /// The part of the code the suppression applies to.
+    pub scope: Scope,
+    /// The names of the suppressed lints; `all` refers to all lints.
+    pub lints: Vec<String>,
+    /// The reason provided for the suppression, if any.
+    pub reason: Option<String>,
+    /// The range of the comment containing the directive.
+    pub range: Range,
+}
+
+
+/// The directives contained in a piece of code.
+#[derive(Debug, Default)]
+pub(crate) struct Directives {
+    /// All directives, along with the range of the comment containing
+    /// them.
+    directives: Vec<(Range, Directive)>,
+    /// Indices of directives applying to the following item, keyed by
+    /// the start byte of the comment containing them.
+    items: HashMap<usize, usize>,
+    /// Indices of directives applying to individual lines, keyed by
+    /// row.
+    lines: HashMap<usize, Vec<usize>>,
+    /// Indices of directives applying to the entire file.
+    file: Vec<usize>,
+    /// Malformed or misplaced directives, along with the range of the
+    /// comment containing them and a description of the problem.
+    invalid: Vec<(Range, String)>,
+    /// The lint names of directives that suppressed a match, as pairs
+    /// of directive index and index into the directive's lints.
+    used: RefCell<HashSet<(usize, usize)>>,
+}
+
+impl Directives {
+    /// Gather all directives from the syntax `tree` of `code`.
+    pub fn new(tree: &Tree, code: &[u8]) -> Self {
+        let mut slf = Self::default();
+        // Whether we are still at the top of the file, i.e., have only
+        // seen comments so far.
+        let mut at_top = true;
+        let mut cursor = tree.walk();
+
+        'outer: loop {
+            let node = cursor.node();
+            if node.kind() == "comment" {
+                let range = Range::from(node.range());
+                match parse_comment(node, code) {
+                    Ok(Some(directive)) => {
+                        let idx = slf.directives.len();
+                        let valid = match directive.action {
+                            Action::Disable | Action::Enable => {
+                                let _prev = slf.items.insert(node.start_byte(), idx);
+                                true
+                            },
+                            Action::DisableNextLine => {
+                                let row = node.end_position().row + 1;
+                                let () = slf.lines.entry(row).or_default().push(idx);
+                                true
+                            },
+                            Action::DisableFile if at_top => {
+                                let () = slf.file.push(idx);
+                                true
+                            },
+                            Action::DisableFile => {
+                                let error = "`disable-file` directives are only supported at the top of the file";
+                                warn!(
+                                    "ignoring directive at {}:{}: {error}",
+                                    range.start_point.row, range.start_point.col
+                                );
+                                let () = slf.invalid.push((range.clone(), error.to_string()));
+                                false
+                            },
+                        };
+                        if valid {
+                            let () = slf.directives.push((range, directive));
+                        }
+                    },
+                    Ok(None) => (),
+                    Err(err) => {
+                        warn!(
+                            "ignoring invalid directive at {}:{}: {err}",
+                            range.start_point.row, range.start_point.col
+                        );
+                        let () = slf.invalid.push((range, err.to_string()));
+                    },
+                }
+            } else if node.parent().is_some() {
+                at_top = false;
+            }
+
+            if cursor.goto_first_child() {
+                continue
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer
+                }
+            }
+        }
+        slf
+    }
+
+    /// Retrieve the directives applying to the entire file.
+    pub fn file(&self) -> impl Iterator<Item = &Directive> {
+        self.file.iter().map(|idx| &self.directives[*idx].1)
+    }
+
+    /// Check whether the directive with index `idx` applies to the lint
+    /// with the given name, marking it as used if `used` is set.
+    fn applies(&self, idx: usize, lint_name: &str, used: bool) -> bool {
+        let (_range, directive) = &self.directives[idx];
+        if let Some(lint_idx) = directive.applies_to(lint_name) {
+            if used {
+                let _inserted = self.used.borrow_mut().insert((idx, lint_idx));
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check whether the lint with the given name is disabled for the
+    /// syntax `node`.
+    ///
+    /// Line based directives take precedence. Otherwise, the innermost
+    /// item directive mentioning the lint decides, with file level
+    /// directives being the fallback. The directive that suppressed the
+    /// lint, if any, is marked as used.
+    pub fn is_disabled(&self, lint_name: &str, mut node: Node<'_>) -> bool {
+        let row = node.start_position().row;
+        if let Some(indices) = self.lines.get(&row) {
+            if indices
+                .iter()
+                .any(|idx| self.applies(*idx, lint_name, true))
+            {
+                return true
+            }
+        }
+
+        loop {
+            // Walk all comments directly preceding the current node.
+            let mut sibling = node.prev_sibling();
+            while let Some(s) = sibling.filter(|s| s.kind() == "comment") {
+                if let Some(idx) = self.items.get(&s.start_byte()) {
+                    let disable = self.directives[*idx].1.action == Action::Disable;
+                    if self.applies(*idx, lint_name, disable) {
+                        return disable
+                    }
+                }
+                sibling = s.prev_sibling();
+            }
+
+            // Move one level up and repeat.
+            match node.parent() {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+
+        self.file
+            .iter()
+            .any(|idx| self.applies(*idx, lint_name, true))
+    }
+
+    /// Retrieve all suppressions, in source code order.
+    pub fn suppressions(&self) -> Vec<Suppression> {
+        self.directives
+            .iter()
+            .filter_map(|(range, directive)| {
+                let scope = match directive.action {
+                    Action::Disable => Scope::Item,
+                    Action::DisableNextLine => Scope::NextLine,
+                    Action::DisableFile => Scope::File,
+                    Action::Enable => return None,
+                };
+                let suppression = Suppression {
+                    scope,
+                    lints: directive.lints.clone(),
+                    reason: directive.reason.clone(),
+                    range: range.clone(),
+                };
+                Some(suppression)
+            })
+            .collect()
+    }
+
+    /// Report suppressing directives that do not provide a reason.
+    pub fn check_reasons(&self, level: Level) -> Vec<LintMatch> {
+        self.suppressions()
+            .into_iter()
+            .filter(|suppression| suppression.reason.is_none())
+            .map(|suppression| LintMatch {
+                lint_name: UNJUSTIFIED_DIRECTIVE.to_string(),
+                message: "directive disables lints without providing a reason (`-- <reason>`)"
+                    .to_string(),
+                range: suppression.range,
+                min_kernel_version: None,
+                level,
+                suggestion: None,
+            })
+            .collect()
+    }
+
+    /// Report directives referring to lints not contained in `known`
+    /// at level `unknown`, those that have not suppressed any match so
+    /// far at level `unused`, and malformed ones at level `invalid`.
+    ///
+    /// Directives only mentioning lints in `ignored` are never
+    /// considered unused.
+    pub fn check(
+        &self,
+        known: &HashSet<String>,
+        ignored: &HashSet<String>,
+        unused: Option<Level>,
+        unknown: Option<Level>,
+        invalid: Option<Level>,
+    ) -> Vec<LintMatch> {
+        let used = self.used.borrow();
+        let mut results = Vec::new();
+
+        if let Some(level) = invalid.filter(|level| *level != Level::Allow) {
+            let invalid = self.invalid.iter().map(|(range, error)| LintMatch {
+                lint_name: INVALID_DIRECTIVE.to_string(),
+                message: format!("directive is ignored: {error}"),
+                range: range.clone(),
+                min_kernel_version: None,
+                level,
+                suggestion: None,
+            });
+            let () = results.extend(invalid);
+        }
+
+        for (idx, (range, directive)) in self.directives.iter().enumerate() {
+            for (lint_idx, lint) in directive.lints.iter().enumerate() {
+                let (lint_name, message, level) = if lint != ALL && !known.contains(lint.as_str()) {
+                    (
+                        UNKNOWN_LINT,
+                        format!("directive refers to unknown lint `{lint}`"),
+                        unknown,
+                    )
+                } else if directive.action != Action::Enable
+                    && !ignored.contains(lint.as_str())
+                    && !used.contains(&(idx, lint_idx))
+                {
+                    (
+                        UNUSED_DIRECTIVE,
+                        format!("directive disables `{lint}`, but no match was suppressed"),
+                        unused,
+                    )
+                } else {
+                    continue
+                };
+
+                let level = match level {
+                    None | Some(Level::Allow) => continue,
+                    Some(level) => level,
+                };
+
+                let r#match = LintMatch {
+                    lint_name: lint_name.to_string(),
+                    message,
+                    range: range.clone(),
+                    min_kernel_version: None,
+                    level,
+                    suggestion: None,
+                };
+                let () = results.push(r#match);
+            }
+        }
+        results
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use tree_sitter::Parser;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    fn parse(code: &str) -> Tree {
+        let mut parser = Parser::new();
+        let () = parser.set_language(&LANGUAGE.into()).unwrap();
+        parser.parse(code, None).unwrap()
+    }
+
+    /// Check that we can parse valid directives.
+    #[test]
+    fn directive_parsing() {
+        let directive = Directive::parse("/* bpflint: disable=probe-read */")
+            .unwrap()
+            .unwrap();
+        assert_eq!(directive.action, Action::Disable);
+        assert_eq!(directive.lints, vec!["probe-read"]);
+
+        let directive = Directive::parse("// bpflint: disable-next-line=foo, bar,baz")
+            .unwrap()
+            .unwrap();
+        assert_eq!(directive.action, Action::DisableNextLine);
+        assert_eq!(directive.lints, vec!["foo", "bar", "baz"]);
+        assert_eq!(directive.applies_to("baz"), Some(2));
+
+        let directive = Directive::parse("/*bpflint:disable-file=all*/")
+            .unwrap()
+            .unwrap();
+        assert_eq!(directive.action, Action::DisableFile);
+        assert_eq!(directive.applies_to("foo"), Some(0));
+
+        let directive = Directive::parse("// bpflint: enable = foo")
+            .unwrap()
+            .unwrap();
+        assert_eq!(directive.action, Action::Enable);
+        assert_eq!(directive.applies_to("foo"), Some(0));
+        assert_eq!(directive.applies_to("bar"), None);
+
+        let directive = Directive::parse("/* bpflint: disable=foo,bar -- it's fine -- really */")
+            .unwrap()
+            .unwrap();
+        assert_eq!(directive.lints, vec!["foo", "bar"]);
+        assert_eq!(directive.reason.as_deref(), Some("it's fine -- really"));
+    }
+
+    /// Check that comments that aren't directives are recognized as
+    /// such and that invalid directives are rejected.
+    #[test]
+    fn invalid_directive_parsing() {
+        assert_eq!(Directive::parse("/* disable=foo */").unwrap(), None);
+        assert_eq!(
+            Directive::parse("// foo bpflint: disable=foo").unwrap(),
+            None
+        );
+
+        let err = Directive::parse("/* bpflint: disabled=foo */").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "directive `disabled=foo` has unknown action `disabled`"
+        );
+        let err = Directive::parse("// bpflint: foo").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "directive `foo` lacks a `=<lint>[,<lint>...]` part"
+        );
+        let err = Directive::parse("// bpflint: disable=foo,,bar").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "directive `disable=foo,,bar` contains an empty lint name"
+        );
+    }
+
+    /// Check that directives are gathered correctly from source code.
+    #[test]
+    fn directive_gathering() {
+        let code = indoc! { r#"
+            // SPDX-License-Identifier: GPL-2.0
+            /* bpflint: disable-file=foo */
+
+            /* bpflint: disable=bar */
+            void test_fn(void) {
+                // bpflint: disable-next-line=baz
+                baz();
+            }
+            /* bpflint: disable-file=baz */
+        "# };
+        let tree = parse(code);
+        let directives = Directives::new(&tree, code.as_bytes());
+        let file = directives.file().collect::<Vec<_>>();
+        assert_eq!(file.len(), 1);
+        assert_eq!(file[0].lints, vec!["foo"]);
+        assert_eq!(directives.items.len(), 1);
+        assert_eq!(
+            directives.lines.keys().copied().collect::<Vec<_>>(),
+            vec![6]
+        );
+    }
+
+    /// Check that malformed and misplaced directives are reported as
+    /// `invalid-directive` matches.
+    #[test]
+    fn invalid_directive_reporting() {
+        let code = indoc! { r#"
+            // bpflint: disabled=probe-read
+            void test_fn(void) {
+                // bpflint: probe-read
+                foo();
+            }
+            /* bpflint: disable-file=foo */
+        "# };
+        let tree = parse(code);
+        let directives = Directives::new(&tree, code.as_bytes());
+        assert!(directives.directives.is_empty());
+
+        let known = HashSet::new();
+        let ignored = HashSet::new();
+        let matches = directives.check(&known, &ignored, None, None, None);
+        assert!(matches.is_empty(), "{matches:?}");
+
+        let matches = directives.check(&known, &ignored, None, None, Some(Level::Warn));
+        assert_eq!(matches.len(), 3, "{matches:?}");
+        assert!(matches.iter().all(|m| m.lint_name == INVALID_DIRECTIVE));
+        assert!(matches.iter().all(|m| m.level == Level::Warn));
+        assert_eq!(matches[0].range.start_point.row, 0);
+        assert_eq!(
+            matches[0].message,
+            "directive is ignored: directive `disabled=probe-read` has unknown action `disabled`"
+        );
+        assert_eq!(matches[1].range.start_point.row, 2);
+        assert_eq!(matches[1].range.start_point.col, 4);
+        assert_eq!(matches[2].range.start_point.row, 5);
+        assert_eq!(
+            &code[matches[2].range.bytes.clone()],
+            "/* bpflint: disable-file=foo */"
+        );
+    }
+}
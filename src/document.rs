@@ -0,0 +1,480 @@
+//! Support for incrementally linting source code as it is edited.
+
+use std::mem::take;
+use std::ops;
+
+use anyhow::Result;
+
+use tree_sitter::InputEdit;
+use tree_sitter::Point as TsPoint;
+use tree_sitter::Tree;
+
+use crate::LintMatch;
+use crate::Linter;
+use crate::Point;
+use crate::Range;
+use crate::directive::Directive;
+use crate::directive::Directives;
+use crate::license::LicenseState;
+use crate::lint::sort_matches;
+
+
+/// An edit to the source code of a [`Document`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// The byte range of the code to replace.
+    ///
+    /// The range refers to the code as it is after all preceding edits
+    /// of the same batch have been applied.
+    pub bytes: ops::Range<usize>,
+    /// The text to replace the range with.
+    pub text: String,
+}
+
+
+/// Calculate the point at byte offset `byte` inside of `code`.
+fn point_at(code: &[u8], byte: usize) -> TsPoint {
+    let code = &code[..byte];
+    let row = code.iter().filter(|&&b| b == b'\n').count();
+    let line_start = code
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    TsPoint {
+        row,
+        column: byte - line_start,
+    }
+}
+
+
+/// Adjust `point` located at or after the end of the code replaced by
+/// `edit`.
+fn shift_point(point: Point, edit: &InputEdit) -> Point {
+    let old_end = edit.old_end_position;
+    let new_end = edit.new_end_position;
+    if point.row == old_end.row {
+        Point {
+            row: new_end.row,
+            col: new_end.column + (point.col - old_end.column),
+        }
+    } else {
+        Point {
+            row: point.row - old_end.row + new_end.row,
+            col: point.col,
+        }
+    }
+}
+
+
+/// Adjust `range` to reflect `edit`.
+///
+/// Returns `false` if the range overlaps with the edited code, in which
+/// case it is left unchanged.
+fn shift_range(range: &mut Range, edit: &InputEdit) -> bool {
+    if range.bytes.end <= edit.start_byte {
+        true
+    } else if range.bytes.start >= edit.old_end_byte {
+        let delta = |byte: usize| byte - edit.old_end_byte + edit.new_end_byte;
+        range.bytes = delta(range.bytes.start)..delta(range.bytes.end);
+        range.start_point = shift_point(range.start_point, edit);
+        range.end_point = shift_point(range.end_point, edit);
+        true
+    } else {
+        false
+    }
+}
+
+
+/// Adjust the byte range `bytes` to reflect `edit`, growing it to cover
+/// the new code if the two overlap.
+fn shift_bytes(bytes: &ops::Range<usize>, edit: &InputEdit) -> ops::Range<usize> {
+    let start = if bytes.start <= edit.start_byte {
+        bytes.start
+    } else if bytes.start >= edit.old_end_byte {
+        bytes.start - edit.old_end_byte + edit.new_end_byte
+    } else {
+        edit.start_byte
+    };
+    let end = if bytes.end <= edit.start_byte {
+        bytes.end
+    } else if bytes.end >= edit.old_end_byte {
+        bytes.end - edit.old_end_byte + edit.new_end_byte
+    } else {
+        edit.new_end_byte
+    };
+    start..end
+}
+
+
+/// Check whether the match `range` is located inside of `bytes`.
+fn is_within(range: &Range, bytes: &ops::Range<usize>) -> bool {
+    // Empty ranges still refer to the character they start at.
+    let end = range.bytes.end.max(range.bytes.start + 1);
+    range.bytes.start < bytes.end && bytes.start < end
+}
+
+
+/// Widen `bytes` to cover all top-level syntax nodes it touches, as
+/// well as the comments and the first other node following them.
+///
+/// Lint queries generally match within a single top-level declaration
+/// (e.g., a function definition), but may be affected by preceding
+/// sibling nodes (comments disabling a lint). Hence, this range is the
+/// one to lint again after the code in `bytes` changed.
+fn widen(tree: &Tree, bytes: ops::Range<usize>) -> ops::Range<usize> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let children = root.children(&mut cursor).collect::<Vec<_>>();
+    let first = children
+        .iter()
+        .position(|child| child.end_byte() >= bytes.start)
+        .unwrap_or(children.len());
+    let last = children[first..]
+        .iter()
+        .rposition(|child| child.start_byte() <= bytes.end)
+        .map(|idx| first + idx + 1)
+        .unwrap_or(first);
+    let mut last = (last + 1).min(children.len());
+    while last < children.len() && children[last - 1].kind() == "comment" {
+        last += 1;
+    }
+
+    let start = children[first..last]
+        .first()
+        .map(|child| child.start_byte().min(bytes.start))
+        .unwrap_or(bytes.start);
+    let end = children[first..last]
+        .last()
+        .map(|child| child.end_byte().max(bytes.end))
+        .unwrap_or(bytes.end);
+    start..end
+}
+
+
+/// Source code along with its lint matches, supporting incremental
+/// re-linting as the code is edited.
+///
+/// When edits are applied, only the parts of the syntax tree that
+/// changed are parsed again and only query matches in the affected
+/// top-level declarations are recomputed. Matches elsewhere are
+/// retained and merely moved to their new location. That makes
+/// re-linting after small edits, as happen while typing in an editor,
+/// cheap even for large source files.
+///
+/// A [`Document`] must always be used with the [`Linter`] it was
+/// created with.
+///
+/// # Examples
+/// ```rust
+/// # use bpflint::builtin_lints;
+/// # use bpflint::Document;
+/// # use bpflint::Edit;
+/// # use bpflint::Linter;
+/// # use bpflint::LintOpts;
+/// let linter = Linter::new(builtin_lints(), LintOpts::default()).unwrap();
+///
+/// let code = "int main(void) {\n    bpf_probe_read(a, b, c);\n}\n";
+/// let mut document = Document::new(&linter, code.into()).unwrap();
+/// assert_eq!(document.matches().len(), 1);
+///
+/// let edit = Edit {
+///     bytes: 21..35,
+///     text: "bpf_probe_read_kernel".to_string(),
+/// };
+/// let matches = document.edit(&linter, &[edit]).unwrap();
+/// assert!(matches.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Document {
+    /// The current source code.
+    code: Vec<u8>,
+    /// The syntax tree of `code`.
+    tree: Tree,
+    /// The lint matches in `code`, in source code order.
+    matches: Vec<LintMatch>,
+    /// The directives in `code` applying to the entire file.
+    file_directives: Vec<Directive>,
+    /// The license related state of `code`.
+    license: LicenseState,
+}
+
+impl Document {
+    /// Create a new [`Document`] for `code`, linting it with `linter`.
+    pub fn new(linter: &Linter, code: Vec<u8>) -> Result<Self> {
+        let tree = linter.parse(&code, None)?;
+        let directives = Directives::new(&tree, &code);
+        let mut matches = linter.lint_tree(&tree, &code, &directives, None)?;
+        let () = sort_matches(&mut matches);
+        let license = LicenseState::new(&tree, &code);
+
+        let slf = Self {
+            code,
+            tree,
+            matches,
+            file_directives: directives.file().cloned().collect(),
+            license,
+        };
+        Ok(slf)
+    }
+
+    /// Apply `edits` to the document and lint it again, reusing as much
+    /// of the previous work as possible.
+    ///
+    /// Edits are applied in order. Returns the updated set of lint
+    /// matches, in source code order.
+    ///
+    /// # Panics
+    /// This method panics if an edit's byte range is out of bounds.
+    pub fn edit(&mut self, linter: &Linter, edits: &[Edit]) -> Result<&[LintMatch]> {
+        let mut matches = take(&mut self.matches);
+        // The byte ranges of new code, which need to be linted again.
+        let mut dirty = Vec::<ops::Range<usize>>::new();
+
+        for Edit { bytes, text } in edits {
+            assert!(
+                bytes.start <= bytes.end && bytes.end <= self.code.len(),
+                "edit range `{bytes:?}` is out of bounds"
+            );
+
+            let new_end_byte = bytes.start + text.len();
+            let start_position = point_at(&self.code, bytes.start);
+            let old_end_position = point_at(&self.code, bytes.end);
+            let _removed = self
+                .code
+                .splice(bytes.clone(), text.bytes())
+                .collect::<Vec<_>>();
+            let edit = InputEdit {
+                start_byte: bytes.start,
+                old_end_byte: bytes.end,
+                new_end_byte,
+                start_position,
+                old_end_position,
+                new_end_position: point_at(&self.code, new_end_byte),
+            };
+
+            let () = self.tree.edit(&edit);
+            let () = matches.retain_mut(|m| {
+                let retain = shift_range(&mut m.range, &edit);
+                if let Some(suggestion) = &mut m.suggestion {
+                    let _shifted = shift_range(&mut suggestion.range, &edit);
+                }
+                retain
+            });
+            let () = dirty
+                .iter_mut()
+                .for_each(|bytes| *bytes = shift_bytes(bytes, &edit));
+            let () = dirty.push(bytes.start..new_end_byte);
+        }
+
+        let tree = linter.parse(&self.code, Some(&self.tree))?;
+        let () = dirty.extend(
+            self.tree
+                .changed_ranges(&tree)
+                .map(|range| range.start_byte..range.end_byte),
+        );
+
+        // A change to file level directives affects the entire file.
+        // And so does any change when checking directives, as a
+        // directive's use may not be confined to a single item.
+        let directives = Directives::new(&tree, &self.code);
+        if linter.checks_directives() || !directives.file().eq(&self.file_directives) {
+            let () = dirty.push(0..self.code.len());
+            self.file_directives = directives.file().cloned().collect();
+        }
+
+        // Lints concerned with the license may report matches anywhere
+        // in the file, so a change in license related state requires
+        // linting everything again.
+        let license = LicenseState::new(&tree, &self.code);
+        if license != self.license {
+            let () = dirty.push(0..self.code.len());
+            self.license = license;
+        }
+
+        let mut dirty = dirty
+            .into_iter()
+            .map(|bytes| widen(&tree, bytes))
+            .collect::<Vec<_>>();
+        let () = dirty.sort_by_key(|bytes| (bytes.start, bytes.end));
+        let dirty = dirty
+            .into_iter()
+            .fold(Vec::<ops::Range<usize>>::new(), |mut merged, bytes| {
+                match merged.last_mut() {
+                    Some(last) if bytes.start <= last.end => {
+                        last.end = last.end.max(bytes.end);
+                    },
+                    _ => merged.push(bytes),
+                }
+                merged
+            });
+
+        let () = matches.retain(|m| !dirty.iter().any(|bytes| is_within(&m.range, bytes)));
+        for bytes in &dirty {
+            let new = linter.lint_tree(&tree, &self.code, &directives, Some(bytes.clone()))?;
+            let () = matches.extend(new.into_iter().filter(|m| is_within(&m.range, bytes)));
+        }
+        let () = sort_matches(&mut matches);
+
+        self.tree = tree;
+        self.matches = matches;
+        Ok(&self.matches)
+    }
+
+    /// Retrieve the current source code.
+    #[inline]
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Retrieve the lint matches in the current source code, in source
+    /// code order.
+    #[inline]
+    pub fn matches(&self) -> &[LintMatch] {
+        &self.matches
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use crate::LintOpts;
+    use crate::builtin_lints;
+
+
+    /// Summarize `matches` in a form that can be compared.
+    fn summarize(matches: &[LintMatch]) -> Vec<(String, Range)> {
+        matches
+            .iter()
+            .map(|m| (m.lint_name.clone(), m.range.clone()))
+            .collect()
+    }
+
+    /// Check that incremental re-linting produces the same results as
+    /// linting from scratch.
+    #[test]
+    fn incremental_linting() {
+        let code = indoc! { r#"
+            struct {
+                __uint(type, BPF_MAP_TYPE_HASH);
+                __uint(key_size, sizeof(u32));
+            } events SEC(".maps");
+
+            SEC("kprobe/do_nanosleep")
+            int handle__do_nanosleep(void *ctx) {
+                bpf_probe_read(a, b, c);
+                return 0;
+            }
+
+            SEC("tp_btf/sched_switch")
+            int handle__sched_switch(u64 *ctx) {
+                struct task_struct *t = (void *)bpf_get_current_task();
+                return 0;
+            }
+        "# };
+
+        let linter = Linter::new(builtin_lints(), LintOpts::default()).unwrap();
+        let mut document = Document::new(&linter, code.into()).unwrap();
+        assert_eq!(document.matches().len(), 4);
+
+        let edit = |document: &Document, pattern: &str, text: &str| {
+            let code = String::from_utf8(document.code().to_vec()).unwrap();
+            let start = code.find(pattern).unwrap();
+            Edit {
+                bytes: start..start + pattern.len(),
+                text: text.to_string(),
+            }
+        };
+
+        let edits = [
+            // Fix a match.
+            ("bpf_probe_read(", "bpf_probe_read_kernel("),
+            // Introduce a new match.
+            ("return 0;", "bpf_probe_read(a, b, c);\n    return 0;"),
+            // Disable a lint for an entire function.
+            (
+                "SEC(\"tp_btf",
+                "/* bpflint: disable=get-current-task */\nSEC(\"tp_btf",
+            ),
+            // Shift everything by a line.
+            ("struct {", "\n\nstruct {"),
+            // Break the code temporarily.
+            (
+                "int handle__do_nanosleep(void *ctx) {",
+                "int handle__do_nanosleep(void *ctx",
+            ),
+            (
+                "int handle__do_nanosleep(void *ctx",
+                "int handle__do_nanosleep(void *ctx) {",
+            ),
+            // Remove the lint disabling comment again.
+            ("/* bpflint: disable=get-current-task */\n", ""),
+            // Disable a lint for the entire file and enable it again.
+            (
+                "\n\nstruct {",
+                "/* bpflint: disable-file=probe-read */\nstruct {",
+            ),
+            ("disable-file=probe-read", "disable-file=untyped-map-member"),
+            // Declare a license that is not GPL compatible, affecting
+            // matches throughout the file.
+            (
+                "} events SEC(\".maps\");",
+                "} events SEC(\".maps\");\n\nchar LICENSE[] SEC(\"license\") = \"MIT\";",
+            ),
+            ("\"MIT\"", "\"Dual MIT/GPL\""),
+        ];
+
+        for (pattern, text) in edits {
+            let edit = edit(&document, pattern, text);
+            let matches = summarize(document.edit(&linter, &[edit]).unwrap());
+            let expected = summarize(&linter.lint(document.code()).unwrap());
+            assert_eq!(
+                matches,
+                expected,
+                "{}",
+                String::from_utf8_lossy(document.code())
+            );
+        }
+    }
+
+    /// Check that multiple edits can be applied at once.
+    #[test]
+    fn batched_editing() {
+        let code = indoc! { r#"
+            int foo(void) {
+                bpf_probe_read(a, b, c);
+            }
+            int bar(void) {
+                bpf_probe_read(a, b, c);
+            }
+        "# };
+
+        let linter = Linter::new(builtin_lints(), LintOpts::default()).unwrap();
+        let mut document = Document::new(&linter, code.into()).unwrap();
+        assert_eq!(document.matches().len(), 2);
+
+        let comment = "/* bpflint: disable=probe-read */\n";
+        // The second edit refers to the code after the first one was
+        // applied.
+        let end = comment.len() + code.rfind(';').unwrap() + 1;
+        let edits = [
+            Edit {
+                bytes: 0..0,
+                text: comment.to_string(),
+            },
+            Edit {
+                bytes: end..end,
+                text: "\n    bpf_probe_read(d, e, f);".to_string(),
+            },
+        ];
+        let matches = summarize(document.edit(&linter, &edits).unwrap());
+        let expected = summarize(&linter.lint(document.code()).unwrap());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches, expected);
+        assert!(matches.iter().all(|(_, range)| range.start_point.row > 3));
+    }
+}
@@ -0,0 +1,101 @@
+//! Functionality for applying fix suggestions to source code.
+
+use crate::Suggestion;
+
+
+/// Apply the provided suggestions to `code`.
+///
+/// Suggestions are applied in source code order. A suggestion that
+/// overlaps with one applied earlier is skipped, as is one that would
+/// not change the code. Returns the modified code along with the
+/// indices of the suggestions that were applied.
+pub(crate) fn apply_suggestions(code: &[u8], suggestions: &[&Suggestion]) -> (Vec<u8>, Vec<usize>) {
+    let mut order = (0..suggestions.len()).collect::<Vec<_>>();
+    let () = order.sort_by_key(|idx| {
+        let bytes = &suggestions[*idx].range.bytes;
+        (bytes.start, bytes.end)
+    });
+
+    let mut fixed = Vec::with_capacity(code.len());
+    let mut applied = Vec::new();
+    // The end of the last applied suggestion's range.
+    let mut end = 0;
+    // Whether an insertion, i.e., a suggestion with an empty range, was
+    // applied at `end`.
+    let mut inserted = false;
+
+    for idx in order {
+        let Suggestion { range, replacement } = suggestions[idx];
+        let bytes = &range.bytes;
+        if bytes.start < end
+            || bytes.end > code.len()
+            || (inserted && bytes.start == end)
+            || code[bytes.clone()] == *replacement.as_bytes()
+        {
+            continue
+        }
+
+        let () = fixed.extend_from_slice(&code[end..bytes.start]);
+        let () = fixed.extend_from_slice(replacement.as_bytes());
+        end = bytes.end;
+        inserted = bytes.is_empty();
+        let () = applied.push(idx);
+    }
+
+    let () = fixed.extend_from_slice(&code[end..]);
+    (fixed, applied)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    fn suggestion(bytes: ops::Range<usize>, replacement: &str) -> Suggestion {
+        Suggestion {
+            range: Range {
+                bytes: bytes.clone(),
+                start_point: Point {
+                    row: 0,
+                    col: bytes.start,
+                },
+                end_point: Point {
+                    row: 0,
+                    col: bytes.end,
+                },
+            },
+            replacement: replacement.to_string(),
+        }
+    }
+
+    /// Check that non-overlapping suggestions are all applied.
+    #[test]
+    fn disjoint_application() {
+        let code = b"foo(); bar();";
+        let bar = suggestion(7..10, "baz");
+        let foo = suggestion(0..3, "foo_btf");
+        let (fixed, applied) = apply_suggestions(code, &[&bar, &foo]);
+        assert_eq!(fixed, b"foo_btf(); baz();");
+        assert_eq!(applied, vec![1, 0]);
+    }
+
+    /// Make sure that overlapping and no-op suggestions are skipped.
+    #[test]
+    fn overlapping_application() {
+        let code = b"foo(); bar();";
+        let call = suggestion(0..5, "foo(1)");
+        let name = suggestion(0..3, "fo");
+        let nop = suggestion(7..10, "bar");
+        let insert1 = suggestion(13..13, " // 1");
+        let insert2 = suggestion(13..13, " // 2");
+        let (fixed, applied) = apply_suggestions(code, &[&call, &name, &nop, &insert1, &insert2]);
+        assert_eq!(fixed, b"fo(); bar(); // 1");
+        assert_eq!(applied, vec![1, 3]);
+    }
+}
@@ -0,0 +1,384 @@
+//! A database of BPF helpers and kfuncs and their availability.
+
+use crate::Version;
+use crate::section::ProgramType;
+
+
+/// Program types that trace kernel or user space execution.
+const TRACING: &[ProgramType] = &[
+    ProgramType::Kprobe,
+    ProgramType::Tracepoint,
+    ProgramType::RawTracepoint,
+    ProgramType::RawTracepointWritable,
+    ProgramType::PerfEvent,
+    ProgramType::Tracing,
+    ProgramType::Lsm,
+];
+
+/// Program types operating on socket buffers.
+const SKB: &[ProgramType] = &[
+    ProgramType::SocketFilter,
+    ProgramType::SchedCls,
+    ProgramType::SchedAct,
+    ProgramType::CgroupSkb,
+    ProgramType::LwtIn,
+    ProgramType::LwtOut,
+    ProgramType::LwtXmit,
+    ProgramType::LwtSeg6local,
+    ProgramType::SkSkb,
+    ProgramType::SkReuseport,
+    ProgramType::FlowDissector,
+];
+
+/// Traffic control program types.
+const TC: &[ProgramType] = &[ProgramType::SchedCls, ProgramType::SchedAct];
+
+/// Program types with a BPF trampoline.
+const TRAMPOLINE: &[ProgramType] = &[ProgramType::Tracing, ProgramType::Lsm];
+
+
+/// A BPF helper or kfunc.
+#[derive(Debug)]
+pub(crate) struct Helper {
+    /// The name of the helper.
+    pub name: &'static str,
+    /// The kernel version that introduced the helper.
+    pub min_kernel_version: Version,
+    /// The program types allowed to call the helper, if restricted.
+    pub program_types: Option<&'static [ProgramType]>,
+    /// Whether the helper can only be called by programs with a GPL
+    /// compatible license.
+    pub gpl_only: bool,
+}
+
+impl Helper {
+    /// Mark the helper as only callable by GPL compatible programs.
+    const fn gpl(mut self) -> Self {
+        self.gpl_only = true;
+        self
+    }
+
+    /// Check whether the helper can be called from a program of type
+    /// `program_type`.
+    pub fn is_allowed_in(&self, program_type: ProgramType) -> bool {
+        self.program_types
+            .is_none_or(|program_types| program_types.contains(&program_type))
+    }
+}
+
+
+/// Describe a helper callable from all program types.
+const fn any(name: &'static str, major: u8, minor: u8) -> Helper {
+    Helper {
+        name,
+        min_kernel_version: Version(major, minor, 0),
+        program_types: None,
+        gpl_only: false,
+    }
+}
+
+/// Describe a helper callable only from the given program types.
+const fn only(
+    name: &'static str,
+    major: u8,
+    minor: u8,
+    program_types: &'static [ProgramType],
+) -> Helper {
+    Helper {
+        name,
+        min_kernel_version: Version(major, minor, 0),
+        program_types: Some(program_types),
+        gpl_only: false,
+    }
+}
+
+/// Describe a kfunc callable from all program types.
+///
+/// The verifier only permits calls to kfuncs from programs with a GPL
+/// compatible license.
+const fn kfunc(name: &'static str, major: u8, minor: u8) -> Helper {
+    any(name, major, minor).gpl()
+}
+
+
+/// Known helpers and kfuncs, sorted by name.
+///
+/// Program type restrictions are only recorded where they are clear
+/// cut, i.e., where they do not depend on kernel version, program
+/// flags, or privileges, so as not to report false positives.
+const HELPERS: &[Helper] = &[
+    only("bpf_bprm_opts_set", 5, 11, &[ProgramType::Lsm]),
+    any("bpf_btf_find_by_name_kind", 5, 14),
+    kfunc("bpf_cast_to_kern_ctx", 6, 2),
+    kfunc("bpf_cgroup_acquire", 6, 2),
+    kfunc("bpf_cgroup_ancestor", 6, 2),
+    kfunc("bpf_cgroup_from_id", 6, 3),
+    kfunc("bpf_cgroup_release", 6, 2),
+    any("bpf_cgrp_storage_delete", 6, 2),
+    any("bpf_cgrp_storage_get", 6, 2),
+    any("bpf_check_mtu", 5, 12),
+    only("bpf_clone_redirect", 4, 2, TC),
+    any("bpf_copy_from_user", 5, 10),
+    any("bpf_copy_from_user_task", 5, 18).gpl(),
+    kfunc("bpf_cpumask_create", 6, 3),
+    any("bpf_csum_diff", 4, 6),
+    any("bpf_csum_level", 5, 8),
+    any("bpf_csum_update", 4, 9),
+    any("bpf_current_task_under_cgroup", 4, 9),
+    only("bpf_d_path", 5, 10, TRAMPOLINE),
+    kfunc("bpf_dynptr_adjust", 6, 5),
+    kfunc("bpf_dynptr_clone", 6, 5),
+    any("bpf_dynptr_data", 5, 19),
+    any("bpf_dynptr_from_mem", 5, 19),
+    // kfunc
+    only("bpf_dynptr_from_skb", 6, 3, SKB).gpl(),
+    // kfunc
+    only("bpf_dynptr_from_xdp", 6, 3, &[ProgramType::Xdp]).gpl(),
+    any("bpf_dynptr_read", 5, 19),
+    kfunc("bpf_dynptr_slice", 6, 3),
+    kfunc("bpf_dynptr_slice_rdwr", 6, 3),
+    any("bpf_dynptr_write", 5, 19),
+    only(
+        "bpf_fib_lookup",
+        4,
+        18,
+        &[
+            ProgramType::SchedCls,
+            ProgramType::SchedAct,
+            ProgramType::Xdp,
+        ],
+    ),
+    only("bpf_find_vma", 5, 17, TRACING),
+    any("bpf_for_each_map_elem", 5, 13),
+    only("bpf_get_branch_snapshot", 5, 16, TRACING).gpl(),
+    any("bpf_get_current_ancestor_cgroup_id", 5, 7),
+    any("bpf_get_current_cgroup_id", 4, 18),
+    any("bpf_get_current_comm", 4, 2),
+    any("bpf_get_current_pid_tgid", 4, 2),
+    any("bpf_get_current_task", 4, 8).gpl(),
+    any("bpf_get_current_task_btf", 5, 11).gpl(),
+    any("bpf_get_current_uid_gid", 4, 2),
+    only("bpf_get_func_arg", 5, 17, TRAMPOLINE),
+    only("bpf_get_func_arg_cnt", 5, 17, TRAMPOLINE),
+    only("bpf_get_func_ip", 5, 15, TRACING).gpl(),
+    only("bpf_get_func_ret", 5, 17, TRAMPOLINE),
+    any("bpf_get_hash_recalc", 4, 8),
+    any("bpf_get_netns_cookie", 5, 7),
+    any("bpf_get_ns_current_pid_tgid", 5, 6),
+    any("bpf_get_numa_node_id", 4, 10),
+    any("bpf_get_prandom_u32", 4, 1),
+    any("bpf_get_smp_processor_id", 4, 1),
+    any("bpf_get_socket_cookie", 4, 12),
+    only("bpf_get_stack", 4, 18, TRACING).gpl(),
+    only("bpf_get_stackid", 4, 6, TRACING).gpl(),
+    any("bpf_get_task_stack", 5, 9),
+    only("bpf_ima_file_hash", 5, 18, &[ProgramType::Lsm]),
+    only("bpf_ima_inode_hash", 5, 11, &[ProgramType::Lsm]),
+    any("bpf_inode_storage_delete", 5, 10),
+    any("bpf_inode_storage_get", 5, 10),
+    kfunc("bpf_iter_num_destroy", 6, 4),
+    kfunc("bpf_iter_num_new", 6, 4),
+    kfunc("bpf_iter_num_next", 6, 4),
+    kfunc("bpf_iter_task_new", 6, 7),
+    any("bpf_jiffies64", 5, 5),
+    any("bpf_kallsyms_lookup_name", 5, 16),
+    any("bpf_kptr_xchg", 5, 19),
+    any("bpf_ktime_get_boot_ns", 5, 8),
+    any("bpf_ktime_get_coarse_ns", 5, 11),
+    any("bpf_ktime_get_ns", 4, 1),
+    any("bpf_ktime_get_tai_ns", 6, 1),
+    only("bpf_l3_csum_replace", 4, 1, SKB),
+    only("bpf_l4_csum_replace", 4, 1, SKB),
+    only("bpf_load_hdr_opt", 5, 10, &[ProgramType::SockOps]),
+    any("bpf_loop", 5, 17),
+    any("bpf_map_delete_elem", 3, 19),
+    any("bpf_map_lookup_elem", 3, 19),
+    any("bpf_map_lookup_percpu_elem", 5, 19),
+    any("bpf_map_peek_elem", 4, 20),
+    any("bpf_map_pop_elem", 4, 20),
+    any("bpf_map_push_elem", 4, 20),
+    kfunc("bpf_map_sum_elem_count", 6, 6),
+    any("bpf_map_update_elem", 3, 19),
+    only("bpf_msg_apply_bytes", 4, 17, &[ProgramType::SkMsg]),
+    only("bpf_msg_cork_bytes", 4, 17, &[ProgramType::SkMsg]),
+    only("bpf_msg_pop_data", 5, 0, &[ProgramType::SkMsg]),
+    only("bpf_msg_pull_data", 4, 17, &[ProgramType::SkMsg]),
+    only("bpf_msg_push_data", 4, 20, &[ProgramType::SkMsg]),
+    only("bpf_msg_redirect_hash", 4, 18, &[ProgramType::SkMsg]),
+    only("bpf_msg_redirect_map", 4, 17, &[ProgramType::SkMsg]),
+    only("bpf_override_return", 4, 16, &[ProgramType::Kprobe]).gpl(),
+    any("bpf_per_cpu_ptr", 5, 10),
+    any("bpf_perf_event_output", 4, 4).gpl(),
+    only("bpf_perf_event_read", 4, 3, TRACING),
+    only("bpf_perf_event_read_value", 4, 15, TRACING),
+    only("bpf_perf_prog_read_value", 4, 15, &[ProgramType::PerfEvent]),
+    kfunc("bpf_preempt_disable", 6, 10),
+    kfunc("bpf_preempt_enable", 6, 10),
+    any("bpf_probe_read", 4, 1).gpl(),
+    any("bpf_probe_read_kernel", 5, 5).gpl(),
+    any("bpf_probe_read_kernel_str", 5, 5).gpl(),
+    any("bpf_probe_read_str", 4, 11).gpl(),
+    any("bpf_probe_read_user", 5, 5).gpl(),
+    any("bpf_probe_read_user_str", 5, 5).gpl(),
+    only("bpf_probe_write_user", 4, 8, TRACING).gpl(),
+    kfunc("bpf_rcu_read_lock", 6, 2),
+    kfunc("bpf_rcu_read_unlock", 6, 2),
+    kfunc("bpf_rdonly_cast", 6, 2),
+    only("bpf_read_branch_records", 5, 6, &[ProgramType::PerfEvent]),
+    only(
+        "bpf_redirect",
+        4,
+        4,
+        &[
+            ProgramType::SchedCls,
+            ProgramType::SchedAct,
+            ProgramType::Xdp,
+        ],
+    ),
+    only("bpf_redirect_map", 4, 14, &[ProgramType::Xdp]),
+    only("bpf_redirect_neigh", 5, 10, TC),
+    only("bpf_redirect_peer", 5, 10, TC),
+    only("bpf_reserve_hdr_opt", 5, 10, &[ProgramType::SockOps]),
+    any("bpf_ringbuf_discard", 5, 8),
+    any("bpf_ringbuf_discard_dynptr", 5, 19),
+    any("bpf_ringbuf_output", 5, 8),
+    any("bpf_ringbuf_query", 5, 8),
+    any("bpf_ringbuf_reserve", 5, 8),
+    any("bpf_ringbuf_reserve_dynptr", 5, 19),
+    any("bpf_ringbuf_submit", 5, 8),
+    any("bpf_ringbuf_submit_dynptr", 5, 19),
+    only("bpf_send_signal", 5, 3, TRACING),
+    only("bpf_send_signal_thread", 5, 5, TRACING),
+    only("bpf_seq_printf", 5, 7, &[ProgramType::Tracing]).gpl(),
+    only("bpf_seq_printf_btf", 5, 10, &[ProgramType::Tracing]).gpl(),
+    only("bpf_seq_write", 5, 7, &[ProgramType::Tracing]).gpl(),
+    any("bpf_set_hash", 4, 13),
+    any("bpf_set_hash_invalid", 4, 9),
+    any("bpf_sk_lookup_tcp", 4, 20),
+    any("bpf_sk_lookup_udp", 4, 20),
+    only("bpf_sk_redirect_hash", 4, 18, &[ProgramType::SkSkb]),
+    only("bpf_sk_redirect_map", 4, 14, &[ProgramType::SkSkb]),
+    any("bpf_sk_release", 4, 20),
+    any("bpf_sk_storage_delete", 5, 2),
+    any("bpf_sk_storage_get", 5, 2),
+    only("bpf_skb_adjust_room", 4, 13, SKB),
+    only("bpf_skb_change_head", 4, 10, SKB),
+    only("bpf_skb_change_proto", 4, 8, SKB),
+    only("bpf_skb_change_tail", 4, 9, SKB),
+    only("bpf_skb_change_type", 4, 8, SKB),
+    only("bpf_skb_ecn_set_ce", 5, 1, SKB),
+    only("bpf_skb_get_tunnel_key", 4, 3, SKB),
+    only("bpf_skb_load_bytes", 4, 5, SKB),
+    only("bpf_skb_load_bytes_relative", 4, 18, SKB),
+    only("bpf_skb_pull_data", 4, 9, SKB),
+    only("bpf_skb_set_tunnel_key", 4, 3, SKB),
+    only("bpf_skb_store_bytes", 4, 1, SKB),
+    only("bpf_skb_vlan_pop", 4, 3, SKB),
+    only("bpf_skb_vlan_push", 4, 3, SKB),
+    any("bpf_snprintf", 5, 13).gpl(),
+    any("bpf_snprintf_btf", 5, 10),
+    only("bpf_sock_ops_cb_flags_set", 4, 16, &[ProgramType::SockOps]),
+    any("bpf_spin_lock", 5, 1),
+    any("bpf_spin_unlock", 5, 1),
+    only("bpf_store_hdr_opt", 5, 10, &[ProgramType::SockOps]),
+    any("bpf_strncmp", 5, 17),
+    any("bpf_strtol", 5, 2),
+    any("bpf_strtoul", 5, 2),
+    any("bpf_sys_bpf", 5, 14),
+    any("bpf_sys_close", 5, 14),
+    only(
+        "bpf_sysctl_get_current_value",
+        5,
+        2,
+        &[ProgramType::CgroupSysctl],
+    ),
+    only("bpf_sysctl_get_name", 5, 2, &[ProgramType::CgroupSysctl]),
+    only(
+        "bpf_sysctl_get_new_value",
+        5,
+        2,
+        &[ProgramType::CgroupSysctl],
+    ),
+    only(
+        "bpf_sysctl_set_new_value",
+        5,
+        2,
+        &[ProgramType::CgroupSysctl],
+    ),
+    any("bpf_tail_call", 4, 2),
+    kfunc("bpf_task_acquire", 6, 2),
+    kfunc("bpf_task_from_pid", 6, 3),
+    any("bpf_task_pt_regs", 5, 15).gpl(),
+    kfunc("bpf_task_release", 6, 2),
+    any("bpf_task_storage_delete", 5, 11),
+    any("bpf_task_storage_get", 5, 11),
+    any("bpf_this_cpu_ptr", 5, 10),
+    kfunc("bpf_throw", 6, 7),
+    any("bpf_timer_cancel", 5, 15).gpl(),
+    any("bpf_timer_init", 5, 15).gpl(),
+    any("bpf_timer_set_callback", 5, 15).gpl(),
+    any("bpf_timer_start", 5, 15).gpl(),
+    any("bpf_trace_printk", 4, 1).gpl(),
+    any("bpf_trace_vprintk", 5, 16).gpl(),
+    any("bpf_user_ringbuf_drain", 6, 1),
+    only("bpf_xdp_adjust_head", 4, 10, &[ProgramType::Xdp]),
+    only("bpf_xdp_adjust_meta", 4, 15, &[ProgramType::Xdp]),
+    only("bpf_xdp_adjust_tail", 4, 18, &[ProgramType::Xdp]),
+    only(
+        "bpf_xdp_get_buff_len",
+        5,
+        18,
+        &[ProgramType::Xdp, ProgramType::Tracing],
+    ),
+    only("bpf_xdp_load_bytes", 5, 18, &[ProgramType::Xdp]),
+    only("bpf_xdp_store_bytes", 5, 18, &[ProgramType::Xdp]),
+];
+
+
+/// Look up the helper or kfunc with the given name.
+pub(crate) fn find(name: &str) -> Option<&'static Helper> {
+    HELPERS
+        .binary_search_by(|helper| helper.name.cmp(name))
+        .ok()
+        .map(|idx| &HELPERS[idx])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Make sure that helpers are sorted by name, as required for
+    /// lookup.
+    #[test]
+    fn helper_order() {
+        for pair in HELPERS.windows(2) {
+            assert!(
+                pair[0].name < pair[1].name,
+                "`{}` and `{}` are not sorted",
+                pair[0].name,
+                pair[1].name
+            );
+        }
+    }
+
+    /// Check that we can look up helpers.
+    #[test]
+    fn helper_lookup() {
+        let helper = find("bpf_loop").unwrap();
+        assert_eq!(helper.min_kernel_version, Version(5, 17, 0));
+        assert!(helper.is_allowed_in(ProgramType::Xdp));
+
+        let helper = find("bpf_xdp_adjust_head").unwrap();
+        assert!(helper.is_allowed_in(ProgramType::Xdp));
+        assert!(!helper.is_allowed_in(ProgramType::Kprobe));
+        assert!(!helper.gpl_only);
+
+        assert!(find("bpf_probe_read_kernel").unwrap().gpl_only);
+        assert!(find("bpf_task_acquire").unwrap().gpl_only);
+
+        assert!(find("bpf_does_not_exist").is_none());
+        assert!(find("printf").is_none());
+    }
+}
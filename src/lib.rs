@@ -22,16 +22,71 @@
 //! will be flagged.
 //!
 //! The directive `bpflint: disable=all` acts as a catch-all, disabling
-//! reporting of all lints.
+//! reporting of all lints. Multiple lints can be disabled at once by
+//! separating their names with commas, e.g., `bpflint:
+//! disable=probe-read,get-current-task`.
+//!
+//! Further directives are available:
+//! - `bpflint: disable-next-line=<lints>` disables lints for the line
+//!   following the comment
+//! - `bpflint: disable-file=<lints>` disables lints for the entire
+//!   file; it is only honored at the top of the file, before any code
+//! - `bpflint: enable=<lints>` enables lints for the following item
+//!   again, after they were disabled for an enclosing block or the file
+//!
+//! ```c
+//! /* bpflint: disable=probe-read */
+//! void handler(void) {
+//!     bpf_probe_read(/* ... */);
+//!     /* bpflint: enable=probe-read */
+//!     {
+//!         /* flagged again */
+//!         bpf_probe_read(/* ... */);
+//!     }
+//!     // bpflint: disable-next-line=probe-read
+//!     bpf_probe_read(/* ... */);
+//! }
+//! ```
+//!
+//! When multiple directives refer to a lint, line based ones take
+//! precedence, followed by the innermost item based one, with file
+//! level directives being the fallback.
+//!
+//! Directives may provide a reason for disabling lints, separated by
+//! `--`:
+//! ```c
+//! /* bpflint: disable=unstable-attach-point -- no tracepoint exists */
+//! ```
+//! Suppressions can be listed by means of [`Linter::suppressions`] and
+//! those lacking a reason can be reported by means of
+//! [`LintOpts::unjustified_directives`].
+//!
+//! Directives that did not suppress any lint match, that refer to
+//! unknown lints, or that are malformed can be reported by means of
+//! [`LintOpts::unused_directives`].
 
 #[cfg(target_arch = "wasm32")]
 #[macro_use]
 mod redefine;
 
+#[cfg(feature = "config")]
+pub mod config;
+mod directive;
+mod document;
+mod fix;
+mod helper;
+mod license;
 mod lines;
 mod lint;
+mod predicate;
 mod report;
+mod ringbuf;
+mod section;
+mod template;
 
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::ops;
 use std::str::FromStr;
 
@@ -59,7 +114,7 @@ pub struct Range {
 }
 
 /// A version in the form of a (major, minor, patch) triple.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Version(pub u8, pub u8, pub u8);
 
 impl FromStr for Version {
@@ -99,14 +154,35 @@ impl FromStr for Version {
     }
 }
 
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self(major, minor, patch) = self;
+        write!(f, "{major}.{minor}.{patch}")
+    }
+}
+
+pub use crate::directive::Scope;
+pub use crate::directive::Suppression;
+pub use crate::document::Document;
+pub use crate::document::Edit;
+pub use crate::lint::Fixed;
+pub use crate::lint::Level;
 pub use crate::lint::Lint;
 pub use crate::lint::LintMatch;
 pub use crate::lint::LintOpts;
+pub use crate::lint::Linter;
+pub use crate::lint::Suggestion;
 pub use crate::lint::builtin_lints;
 pub use crate::lint::lint;
 pub use crate::lint::lint_custom;
 pub use crate::lint::lint_custom_opts;
+pub use crate::lint::load_lints_from_dir;
+pub use crate::report::json;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::report::sarif;
 pub use crate::report::terminal;
+pub use crate::section::ProgramType;
+pub use crate::section::Section;
 
 
 #[cfg(target_arch = "wasm32")]
@@ -159,11 +235,17 @@ mod wasm {
                     lint_name,
                     message,
                     range,
+                    min_kernel_version,
+                    level,
+                    suggestion,
                 } = m;
                 let m = LintMatch {
                     lint_name: escape_html(&lint_name).into_owned(),
                     message: escape_html(&message).into_owned(),
                     range,
+                    min_kernel_version,
+                    level,
+                    suggestion,
                 };
                 let path = escape_html(&path);
                 let escaped_path = Path::new(path.as_ref());
@@ -230,6 +312,14 @@ mod tests {
         assert_ne!(Version(1, 0, 0), Version(0, 0, 0));
     }
 
+    /// Check that versions are displayed in their parseable form.
+    #[test]
+    fn version_display() {
+        let version = Version(5, 4, 0);
+        assert_eq!(version.to_string(), "5.4.0");
+        assert_eq!(Version::from_str(&version.to_string()).unwrap(), version);
+    }
+
     /// Make sure that versions can be ordered properly.
     #[test]
     fn version_ordering() {
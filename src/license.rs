@@ -0,0 +1,286 @@
+//! Discovery of the license declared by a BPF object file.
+
+use std::str;
+
+use tree_sitter::Node;
+use tree_sitter::Tree;
+
+
+/// The name of the section containing the license.
+const LICENSE_SECTION: &str = "license";
+
+/// License strings considered GPL compatible by the kernel.
+///
+/// The list mirrors `license_is_gpl_compatible` in the kernel's
+/// `include/linux/license.h`.
+const GPL_COMPATIBLE: &[&str] = &[
+    "GPL",
+    "GPL v2",
+    "GPL and additional rights",
+    "Dual BSD/GPL",
+    "Dual MIT/GPL",
+    "Dual MPL/GPL",
+];
+
+
+/// Check whether `license` is considered GPL compatible by the kernel.
+fn is_gpl_compatible(license: &str) -> bool {
+    GPL_COMPATIBLE.contains(&license)
+}
+
+
+/// Retrieve the contents of the string literal `node`, which may also
+/// be a concatenation of multiple string literals.
+fn string_contents(node: Node<'_>, code: &[u8]) -> Option<String> {
+    match node.kind() {
+        "string_literal" => str::from_utf8(&code[node.byte_range()])
+            .ok()?
+            .strip_prefix('"')?
+            .strip_suffix('"')
+            .map(str::to_string),
+        "concatenated_string" => {
+            let mut cursor = node.walk();
+            let contents = node
+                .named_children(&mut cursor)
+                .map(|child| string_contents(child, code))
+                .collect::<Option<Vec<_>>>()?;
+            Some(contents.concat())
+        },
+        _ => None,
+    }
+}
+
+
+/// Find the first string literal starting at or after `start` in the
+/// tree rooted at `node`.
+fn find_string(node: Node<'_>, start: usize, code: &[u8]) -> Option<Option<String>> {
+    if node.end_byte() <= start {
+        return None
+    }
+
+    if node.start_byte() >= start && matches!(node.kind(), "string_literal" | "concatenated_string")
+    {
+        return Some(string_contents(node, code))
+    }
+
+    let mut cursor = node.walk();
+    let result = node
+        .named_children(&mut cursor)
+        .find_map(|child| find_string(child, start, code));
+    result
+}
+
+
+/// The license declared by a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum License {
+    /// The file does not declare a license.
+    Missing,
+    /// The file declares a license, but it could not be determined,
+    /// e.g., because it is provided by a macro.
+    Unknown,
+    /// The file declares the contained license.
+    Declared(String),
+}
+
+impl License {
+    /// Determine the license declared in the file with syntax tree root
+    /// `root`.
+    pub fn new(root: Node<'_>, code: &[u8]) -> Self {
+        let mut cursor = root.walk();
+        let children = root.named_children(&mut cursor).collect::<Vec<_>>();
+
+        for (idx, child) in children.iter().enumerate() {
+            if child.kind() == "function_definition" {
+                continue
+            }
+
+            let Some(sec) = find_license_specifier(*child, code) else {
+                continue
+            };
+
+            // Depending on how well the declaration could be parsed,
+            // the license string may be part of the same or of the
+            // next top-level node.
+            let license = children[idx..]
+                .iter()
+                .take(2)
+                .filter(|node| node.kind() != "function_definition")
+                .find_map(|node| find_string(*node, sec.end_byte(), code));
+            return match license {
+                Some(Some(license)) => Self::Declared(license),
+                Some(None) | None => Self::Unknown,
+            }
+        }
+        Self::Missing
+    }
+
+    /// Check whether the license is known to not be GPL compatible.
+    pub fn is_gpl_incompatible(&self) -> bool {
+        match self {
+            Self::Missing | Self::Unknown => false,
+            Self::Declared(license) => !is_gpl_compatible(license),
+        }
+    }
+}
+
+
+/// Check whether the call expression `node` is a `section("license")`
+/// attribute, as used in `__attribute__((section("license"), used))`.
+fn is_license_attribute(node: Node<'_>, code: &[u8]) -> Option<bool> {
+    let function = node.child_by_field_name("function")?;
+    if &code[function.byte_range()] != b"section" {
+        return Some(false)
+    }
+    let arguments = node.child_by_field_name("arguments")?;
+    let section = string_contents(arguments.named_child(0)?, code)?;
+    Some(section == LICENSE_SECTION)
+}
+
+
+/// Find a `sec_specifier` or `section` attribute for the license
+/// section in the tree rooted at `node`.
+fn find_license_specifier<'tree>(node: Node<'tree>, code: &[u8]) -> Option<Node<'tree>> {
+    if node.kind() == "sec_specifier" {
+        let value = node.child_by_field_name("value")?;
+        let section = string_contents(value, code)?;
+        return (section == LICENSE_SECTION).then_some(node)
+    }
+
+    if node.kind() == "attribute_specifier" {
+        let mut cursor = node.walk();
+        let arguments = node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "argument_list")?;
+        let mut cursor = arguments.walk();
+        let attribute = arguments.named_children(&mut cursor).find(|attribute| {
+            attribute.kind() == "call_expression"
+                && is_license_attribute(*attribute, code).unwrap_or(false)
+        });
+        return attribute
+    }
+
+    let mut cursor = node.walk();
+    let result = node
+        .named_children(&mut cursor)
+        .find_map(|child| find_license_specifier(child, code));
+    result
+}
+
+
+/// Find the first function definition with a `SEC()` annotation, i.e.,
+/// the first BPF program, in the file with syntax tree root `root`.
+pub(crate) fn first_program(root: Node<'_>) -> Option<Node<'_>> {
+    let mut cursor = root.walk();
+    let program = root.named_children(&mut cursor).find(|child| {
+        let mut cursor = child.walk();
+        child.kind() == "function_definition"
+            && child
+                .named_children(&mut cursor)
+                .any(|child| child.kind() == "sec_specifier")
+    });
+    program
+}
+
+
+/// The license related state of a file.
+///
+/// Lint matches anywhere in a file may depend on this state and so all
+/// of them have to be recomputed when it changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LicenseState {
+    /// The declared license.
+    license: License,
+    /// The start of the first BPF program, if the license is missing.
+    first_program: Option<usize>,
+}
+
+impl LicenseState {
+    /// Capture the license related state of the file with the given
+    /// syntax tree.
+    pub fn new(tree: &Tree, code: &[u8]) -> Self {
+        let root = tree.root_node();
+        let license = License::new(root, code);
+        let first_program = if license == License::Missing {
+            first_program(root).map(|program| program.start_byte())
+        } else {
+            None
+        };
+
+        Self {
+            license,
+            first_program,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tree_sitter::Parser;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    /// Determine the license declared in `code`.
+    fn license(code: &str) -> License {
+        let mut parser = Parser::new();
+        let () = parser.set_language(&LANGUAGE.into()).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        License::new(tree.root_node(), code.as_bytes())
+    }
+
+
+    /// Check that we can find the declared license.
+    #[test]
+    fn license_discovery() {
+        assert_eq!(
+            license(r#"char LICENSE[] SEC("license") = "Dual BSD/GPL";"#),
+            License::Declared("Dual BSD/GPL".to_string())
+        );
+        assert_eq!(
+            license(r#"char _license[4] SEC("license") = "GP" "L";"#),
+            License::Declared("GPL".to_string())
+        );
+        assert_eq!(
+            license(r#"char LICENSE[] SEC("license") = LICENSE_STR;"#),
+            License::Unknown
+        );
+        assert_eq!(
+            license(r#"char _license[] __attribute__((section("license"), used)) = "GPL";"#),
+            License::Declared("GPL".to_string())
+        );
+        assert_eq!(
+            license(
+                r#"static const char _license[] __attribute__((used, section("license"))) = "MIT";"#
+            ),
+            License::Declared("MIT".to_string())
+        );
+        assert_eq!(
+            license(r#"int x __attribute__((section(".data.x"))) = 1;"#),
+            License::Missing
+        );
+        assert_eq!(
+            license(
+                r#"
+                struct { int a; } events SEC(".maps");
+                SEC("xdp")
+                int prog(void *ctx) { return 0; }
+            "#
+            ),
+            License::Missing
+        );
+    }
+
+    /// Check that GPL compatibility is determined correctly.
+    #[test]
+    fn gpl_compatibility() {
+        assert!(!License::Declared("GPL".to_string()).is_gpl_incompatible());
+        assert!(!License::Declared("Dual MIT/GPL".to_string()).is_gpl_incompatible());
+        assert!(License::Declared("MIT".to_string()).is_gpl_incompatible());
+        assert!(License::Declared("GPL-2.0".to_string()).is_gpl_incompatible());
+        assert!(!License::Missing.is_gpl_incompatible());
+        assert!(!License::Unknown.is_gpl_incompatible());
+    }
+}
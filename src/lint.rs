@@ -1,11 +1,21 @@
-use std::str;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::read_dir;
+use std::fs::read_to_string;
+use std::ops;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::PoisonError;
 
 use anyhow::Context as _;
 use anyhow::Result;
 
 use tracing::warn;
 
-use tree_sitter::Node;
 use tree_sitter::Parser;
 use tree_sitter::Query;
 use tree_sitter::QueryCursor;
@@ -16,6 +26,16 @@ use tree_sitter_bpf_c::LANGUAGE;
 use crate::Point;
 use crate::Range;
 use crate::Version;
+use crate::directive::Directives;
+use crate::directive::INVALID_DIRECTIVE;
+use crate::directive::Suppression;
+use crate::directive::UNJUSTIFIED_DIRECTIVE;
+use crate::directive::UNKNOWN_LINT;
+use crate::directive::UNUSED_DIRECTIVE;
+use crate::fix::apply_suggestions;
+use crate::predicate::Context;
+use crate::predicate::Predicate;
+use crate::template::Template;
 
 
 mod lints {
@@ -47,6 +67,13 @@ impl From<tree_sitter::Range> for Range {
 
 
 /// The representation of a lint.
+///
+/// A lint's query may attach a replacement template to a pattern by
+/// means of a `fix` property, e.g., `(#set! "fix" "{{function}}_btf")`.
+/// Matches of the pattern then carry a [`Suggestion`] replacing the
+/// reported capture with the expanded template. Templates can reference
+/// other captures of the match as `{{capture}}` and strip a suffix from
+/// the captured code using `{{capture%suffix}}`.
 #[derive(Clone, Debug)]
 pub struct Lint {
     /// The lint's name.
@@ -60,6 +87,42 @@ pub struct Lint {
     pub message: String,
 }
 
+impl Lint {
+    /// Load a lint from the files at `query_path`, containing the
+    /// lint's [tree-sitter query][query], and `message_path`,
+    /// containing its message.
+    ///
+    /// The lint's name is inferred from the query file's name, minus
+    /// extension. E.g., a query file `lints/probe-read.scm` results in
+    /// a lint named `probe-read`.
+    ///
+    /// [query]: https://tree-sitter.github.io/tree-sitter/using-parsers/queries/
+    pub fn from_files(query_path: &Path, message_path: &Path) -> Result<Self> {
+        let name = query_path
+            .file_stem()
+            .with_context(|| format!("lint path `{}` has no file name", query_path.display()))?
+            .to_str()
+            .with_context(|| {
+                format!(
+                    "lint `{}` does not have valid UTF-8 name",
+                    query_path.display()
+                )
+            })?
+            .to_string();
+        let code = read_to_string(query_path)
+            .with_context(|| format!("failed to read lint `{}`", query_path.display()))?;
+        let message = read_to_string(message_path)
+            .with_context(|| format!("failed to read lint message `{}`", message_path.display()))?;
+
+        let slf = Self {
+            name,
+            code,
+            message: message.trim_end_matches('\n').to_string(),
+        };
+        Ok(slf)
+    }
+}
+
 impl AsRef<Lint> for Lint {
     #[inline]
     fn as_ref(&self) -> &Lint {
@@ -67,11 +130,70 @@ impl AsRef<Lint> for Lint {
     }
 }
 
+/// The level at which matches of a lint are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Level {
+    /// The lint is not checked and its matches are never reported.
+    Allow,
+    /// Matches are reported as warnings.
+    #[default]
+    Warn,
+    /// Matches are reported as errors.
+    Deny,
+}
+
+impl Level {
+    /// Retrieve the level at which the lint with the provided name is
+    /// reported when none is configured explicitly.
+    pub(crate) fn default_for(name: &str) -> Self {
+        if ALLOWED_BY_DEFAULT.contains(&name) {
+            Self::Allow
+        } else {
+            Self::default()
+        }
+    }
+}
+
+
 /// Configuration options for lints.
 #[derive(Default, Clone, Debug)]
 pub struct LintOpts {
     /// The minimum kernel version being targeted.
     pub kernel_version: Option<Version>,
+    /// The levels of individual lints, keyed by lint name. Lints not
+    /// present are reported at [`Level::Warn`], with the exception of
+    /// `missing-license`, which is only checked when configured
+    /// explicitly.
+    ///
+    /// Levels of the `parse-error`, `unused-directive`, `unknown-lint`,
+    /// and `unjustified-directive` matches reported by the linter
+    /// itself may be contained as well, taking precedence over the
+    /// dedicated options below.
+    pub levels: HashMap<String, Level>,
+    /// The level at which syntax errors in the linted code are
+    /// reported, if at all.
+    ///
+    /// Lints cannot detect issues in code that failed to parse. When
+    /// set, such code is reported as a `parse-error` match, making it
+    /// apparent where the linter is effectively blind.
+    pub parse_errors: Option<Level>,
+    /// The level at which problematic `bpflint:` directives are
+    /// reported, if at all.
+    ///
+    /// When set, directives that did not suppress any match are
+    /// reported as `unused-directive` matches, directives referring
+    /// to lints unknown to the linter are reported as `unknown-lint`
+    /// matches, and malformed or misplaced directives are reported as
+    /// `invalid-directive` matches.
+    pub unused_directives: Option<Level>,
+    /// The level at which `bpflint:` directives disabling lints without
+    /// providing a reason (`-- <reason>`) are reported as
+    /// `unjustified-directive` matches, if at all.
+    pub unjustified_directives: Option<Level>,
+    /// The names of lints reported by the application itself (e.g.,
+    /// based on the file name), which `bpflint:` directives may refer
+    /// to without being reported as `unknown-lint` matches.
+    pub synthetic_lints: Vec<String>,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -88,6 +210,45 @@ pub fn builtin_lints() -> impl ExactSizeIterator<Item = Lint> + DoubleEndedItera
 }
 
 
+/// Load all lints contained in the directory at `dir`.
+///
+/// Lints follow the same convention as those shipped with the library:
+/// each consists of a `<name>.scm` file containing the [tree-sitter
+/// query][query] and a `<name>.txt` file next to it, containing the
+/// message to report. Lints are returned sorted by name.
+///
+/// [query]: https://tree-sitter.github.io/tree-sitter/using-parsers/queries/
+pub fn load_lints_from_dir(dir: &Path) -> Result<Vec<Lint>> {
+    let mut lints = Vec::new();
+    for result in
+        read_dir(dir).with_context(|| format!("failed to read directory `{}`", dir.display()))?
+    {
+        let entry = result
+            .with_context(|| format!("failed to read directory entry in `{}`", dir.display()))?;
+        let query_path = entry.path();
+        if query_path.extension() != Some(OsStr::new("scm")) {
+            continue
+        }
+
+        let message_path = query_path.with_extension("txt");
+        let lint = Lint::from_files(&query_path, &message_path)?;
+        let () = lints.push(lint);
+    }
+    let () = lints.sort_by(|lint1, lint2| lint1.name.cmp(&lint2.name));
+    Ok(lints)
+}
+
+
+/// A suggested edit for addressing a lint match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The code range to replace.
+    pub range: Range,
+    /// The code to replace the range with.
+    pub replacement: String,
+}
+
+
 /// Details about a lint match.
 #[derive(Clone, Debug)]
 pub struct LintMatch {
@@ -97,50 +258,210 @@ pub struct LintMatch {
     pub message: String,
     /// The code range that triggered the lint.
     pub range: Range,
+    /// The minimum kernel version required by the lint pattern that
+    /// matched, if any.
+    pub min_kernel_version: Option<Version>,
+    /// The level at which the match is reported.
+    pub level: Level,
+    /// A suggested edit fixing the issue, if the lint provides one.
+    pub suggestion: Option<Suggestion>,
 }
 
 
-/// Walk the syntax tree, checking if a comment node that disable the
-/// given lint is present.
-fn is_lint_disabled(lint_name: &str, mut node: Node, code: &[u8]) -> bool {
-    loop {
-        // Walk all previous siblings of the current node.
-        if let Some(s) = node.prev_sibling() {
-            if s.kind() == "comment" {
-                let comment = &code[s.start_byte()..s.end_byte()];
-                if let Ok(comment) = str::from_utf8(comment) {
-                    // The comment node will still contain the actual
-                    // comment syntax, unfortunately.
-                    let comment = comment.trim_start_matches("//");
-                    let comment = comment.trim_start_matches("/*");
-                    let comment = comment.trim_end_matches("*/");
-                    let comment = comment.trim();
-
-                    if let Some(comment) = comment.strip_prefix("bpflint:") {
-                        let directive = comment.trim();
-                        match directive.strip_prefix("disable=") {
-                            Some("all") => break true,
-                            Some(disable) if disable == lint_name => break true,
-                            _ => (),
-                        }
-                    }
-                } else {
-                    // If it's not valid UTF-8 it can't be a comment for
-                    // us to consider.
-                    warn!(
-                        "encountered invalid UTF-8 in code comment at bytes `{}..{}`",
-                        s.start_byte(),
-                        s.end_byte()
-                    );
-                }
+/// The name under which syntax errors are reported.
+const PARSE_ERROR: &str = "parse-error";
+
+/// The names of matches reported by the linter itself, as opposed to
+/// by lint queries.
+pub(crate) const SYNTHETIC_LINTS: &[&str] = &[
+    INVALID_DIRECTIVE,
+    PARSE_ERROR,
+    UNJUSTIFIED_DIRECTIVE,
+    UNKNOWN_LINT,
+    UNUSED_DIRECTIVE,
+];
+
+/// The names of built-in lints that are only checked when configured
+/// at a level other than [`Level::Allow`] explicitly.
+const ALLOWED_BY_DEFAULT: &[&str] = &["missing-license"];
+
+
+/// The maximum number of times fixes are applied to a piece of code,
+/// to pick up fixes skipped earlier due to overlaps.
+const MAX_FIX_PASSES: usize = 10;
+
+
+/// The result of fixing source code.
+#[derive(Clone, Debug)]
+pub struct Fixed {
+    /// The fixed source code.
+    pub code: Vec<u8>,
+    /// The lint matches that were fixed.
+    ///
+    /// Note that ranges refer to the code as it was when the respective
+    /// fix was applied, which is not necessarily the original one.
+    pub fixed: Vec<LintMatch>,
+    /// The lint matches remaining in the fixed source code.
+    pub matches: Vec<LintMatch>,
+}
+
+
+/// Report syntax errors in `tree` as matches at the given `level`,
+/// optionally only considering those intersecting with `bytes`.
+fn parse_errors(
+    tree: &Tree,
+    directives: &Directives,
+    level: Level,
+    bytes: Option<&ops::Range<usize>>,
+) -> Vec<LintMatch> {
+    let mut results = Vec::new();
+    let mut cursor = tree.walk();
+    let mut nodes = vec![tree.root_node()];
+
+    while let Some(node) = nodes.pop() {
+        if let Some(bytes) = bytes {
+            if node.end_byte() < bytes.start || node.start_byte() > bytes.end {
+                continue
+            }
+        }
+
+        if node.is_error() || node.is_missing() {
+            if directives.is_disabled(PARSE_ERROR, node) {
+                continue
             }
+
+            let message = if node.is_missing() {
+                format!(
+                    "code could not be parsed (missing `{}`), so lints may not detect issues in it",
+                    node.kind()
+                )
+            } else {
+                "code could not be parsed, so lints may not detect issues in it".to_string()
+            };
+            let r#match = LintMatch {
+                lint_name: PARSE_ERROR.to_string(),
+                message,
+                range: Range::from(node.range()),
+                min_kernel_version: None,
+                level,
+                suggestion: None,
+            };
+            let () = results.push(r#match);
+        } else if node.has_error() {
+            let () = nodes.extend(node.children(&mut cursor));
         }
+    }
+    results
+}
+
 
-        // Move one level up and repeat.
-        match node.parent() {
-            Some(parent) => node = parent,
-            None => break false,
+/// Information about a single pattern of a [`CompiledLints`] query.
+#[derive(Debug)]
+struct Pattern {
+    /// The index of the lint the pattern originates from.
+    lint: usize,
+    /// The minimum kernel version required by the pattern, if any.
+    min_kernel_version: Option<Version>,
+    /// The replacement template of the pattern, if any.
+    fix: Option<Template>,
+    /// The custom predicates a match of the pattern has to satisfy.
+    predicates: Vec<Predicate>,
+}
+
+
+/// A set of lints, with their queries compiled into a single one.
+///
+/// Having a single query means that a syntax tree has to be traversed
+/// only once, irrespective of the number of lints.
+#[derive(Debug)]
+struct CompiledLints {
+    /// The lints along with the level at which their matches are
+    /// reported.
+    lints: Vec<(Lint, Level)>,
+    /// The combined query of all lints.
+    query: Query,
+    /// Information about each query pattern, indexed by pattern index.
+    patterns: Vec<Pattern>,
+}
+
+impl CompiledLints {
+    fn new(lints: Vec<(Lint, Level)>) -> Result<Self> {
+        let mut source = String::new();
+        let mut queries = Vec::with_capacity(lints.len());
+        for (lint, _level) in &lints {
+            // We compile each lint's query on its own as well, to
+            // attribute errors to the offending lint and to make sure
+            // that lints don't accidentally reference each other's
+            // captures.
+            let query = Query::new(&LANGUAGE.into(), &lint.code)
+                .with_context(|| format!("failed to compile query of lint `{}`", lint.name))?;
+            let () = queries.push(query);
+            let () = source.push_str(&lint.code);
+            // Make sure that a trailing comment does not swallow the
+            // next lint's query.
+            let () = source.push('\n');
+        }
+
+        let query = Query::new(&LANGUAGE.into(), &source)
+            .context("failed to compile combined lint query")?;
+        let mut patterns = Vec::with_capacity(query.pattern_count());
+        for (idx, ((lint, _level), lint_query)) in lints.iter().zip(&queries).enumerate() {
+            for _ in 0..lint_query.pattern_count() {
+                let pattern_idx = patterns.len();
+                let property = |key| {
+                    query
+                        .property_settings(pattern_idx)
+                        .iter()
+                        .find(|prop| prop.key.as_ref() == key)
+                        .and_then(|prop| prop.value.as_deref())
+                };
+
+                let min_kernel_version = property("min_kernel_version")
+                    .map(str::parse::<Version>)
+                    .transpose()
+                    .with_context(|| {
+                        format!(
+                            "lint `{}` specifies invalid `min_kernel_version` property",
+                            lint.name
+                        )
+                    })?;
+                let fix = property("fix")
+                    .map(|template| {
+                        // Validate against the lint's own query, but
+                        // resolve captures using the combined one.
+                        let _template = Template::parse(template, lint_query)?;
+                        Template::parse(template, &query)
+                    })
+                    .transpose()
+                    .with_context(|| {
+                        format!("lint `{}` specifies invalid `fix` property", lint.name)
+                    })?;
+                let predicates = query
+                    .general_predicates(pattern_idx)
+                    .iter()
+                    .map(Predicate::new)
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| format!("lint `{}` uses invalid predicate", lint.name))?;
+
+                let pattern = Pattern {
+                    lint: idx,
+                    min_kernel_version,
+                    fix,
+                    predicates,
+                };
+                let () = patterns.push(pattern);
+            }
         }
+        // SANITY: Queries are self-contained and so concatenating them
+        //         preserves all patterns.
+        debug_assert_eq!(patterns.len(), query.pattern_count());
+
+        let slf = Self {
+            lints,
+            query,
+            patterns,
+        };
+        Ok(slf)
     }
 }
 
@@ -148,40 +469,56 @@ fn is_lint_disabled(lint_name: &str, mut node: Node, code: &[u8]) -> bool {
 fn lint_impl(
     tree: &Tree,
     code: &[u8],
-    lint: &Lint,
+    directives: &Directives,
+    compiled: &CompiledLints,
     lint_opts: &LintOpts,
+    bytes: Option<ops::Range<usize>>,
 ) -> Result<Vec<LintMatch>> {
-    let Lint {
-        name: lint_name,
-        code: lint_src,
-        message: lint_msg,
-    } = lint;
-
-    let query =
-        Query::new(&LANGUAGE.into(), lint_src).with_context(|| "failed to compile lint query")?;
+    let CompiledLints {
+        lints,
+        query,
+        patterns,
+    } = compiled;
+
     let mut query_cursor = QueryCursor::new();
+    if let Some(bytes) = bytes {
+        let _cursor = query_cursor.set_byte_range(bytes);
+    }
+    let ctx = Context::new(code, tree.root_node(), lint_opts.kernel_version);
     let mut results = Vec::new();
-    let mut matches = query_cursor.matches(&query, tree.root_node(), code);
+    let mut matches = query_cursor.matches(query, tree.root_node(), code);
     while let Some(m) = matches.next() {
-        for capture in m.captures {
-            if is_lint_disabled(lint_name, capture.node, code) {
-                continue;
-            }
+        let Pattern {
+            lint,
+            min_kernel_version,
+            fix,
+            predicates,
+        } = &patterns[m.pattern_index];
+        let mut suggestions = Vec::new();
+        if !predicates
+            .iter()
+            .all(|predicate| predicate.eval(m.captures, &ctx, &mut suggestions))
+        {
+            continue
+        }
 
-            let settings = query.property_settings(m.pattern_index);
-            let min_kernel_version = settings
-                .iter()
-                .find(|prop| prop.key.as_ref() == "min_kernel_version")
-                .and_then(|prop| prop.value.as_deref());
-            let min_kernel_version = min_kernel_version.map(str::parse::<Version>).transpose()?;
+        let (
+            Lint {
+                name: lint_name,
+                code: _,
+                message: lint_msg,
+            },
+            level,
+        ) = &lints[*lint];
 
+        for capture in m.captures {
             // Check that min kernel version from the lint is less than
             // the user specified kernel version. If no version is specified
             // for the lint, we default to running it.
             if let (Some(min_kernel_version), Some(kernel_version)) =
                 (min_kernel_version, lint_opts.kernel_version)
             {
-                if kernel_version < min_kernel_version {
+                if kernel_version < *min_kernel_version {
                     continue;
                 }
             }
@@ -195,10 +532,32 @@ fn lint_impl(
                 continue
             }
 
+            if directives.is_disabled(lint_name, capture.node) {
+                continue;
+            }
+
+            let range = Range::from(capture.node.range());
+            let suggestion = fix
+                .as_ref()
+                .and_then(|template| template.expand(m.captures, code))
+                .or_else(|| {
+                    suggestions
+                        .iter()
+                        .find(|(node, _replacement)| *node == capture.node)
+                        .map(|(_node, replacement)| replacement.clone())
+                })
+                .map(|replacement| Suggestion {
+                    range: range.clone(),
+                    replacement,
+                });
+
             let r#match = LintMatch {
                 lint_name: lint_name.to_string(),
                 message: lint_msg.to_string(),
-                range: Range::from(capture.node.range()),
+                range,
+                min_kernel_version: *min_kernel_version,
+                level: *level,
+                suggestion,
             };
             let () = results.push(r#match);
         }
@@ -211,6 +570,343 @@ fn lint_impl(
 }
 
 
+/// Sort lint matches to ensure more consistent reporting with
+/// ascending lines.
+pub(crate) fn sort_matches(matches: &mut [LintMatch]) {
+    let () = matches.sort_by(|match1, match2| {
+        // NB: We use an ad-hoc comparison rather than a proper
+        // `PartialOrd` impl for `Range`, because the latter is a bit
+        // harder to do correctly.
+        match1
+            .range
+            .start_point
+            .cmp(&match2.range.start_point)
+            .then_with(|| match1.range.end_point.cmp(&match2.range.end_point))
+    });
+}
+
+
+/// A reusable linter for checking code against a fixed set of lints.
+///
+/// Lint queries are compiled once, when the `Linter` is created, and
+/// parser state is cached between invocations. Hence, when linting
+/// many source files, using a `Linter` is more efficient than repeated
+/// calls to [`lint_custom_opts`].
+///
+/// # Examples
+/// ```rust
+/// # use bpflint::builtin_lints;
+/// # use bpflint::Linter;
+/// # use bpflint::LintOpts;
+/// let linter = Linter::new(builtin_lints(), LintOpts::default()).unwrap();
+///
+/// let code = br#"
+///     SEC("kprobe/do_nanosleep")
+///     int handle__do_nanosleep(void *ctx) {
+///         return 0;
+///     }
+/// "#;
+/// let matches = linter.lint(code).unwrap();
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].lint_name, "unstable-attach-point");
+/// ```
+pub struct Linter {
+    /// The compiled lints to check code against.
+    lints: CompiledLints,
+    /// The names of all lints known to the linter, including those
+    /// that are not checked.
+    known: HashSet<String>,
+    /// The names of known lints that are not checked or for which it
+    /// is not tracked whether directives suppressed any of their
+    /// matches.
+    unchecked: HashSet<String>,
+    /// The options to use when linting.
+    opts: LintOpts,
+    /// The level at which directives referring to unknown lints are
+    /// reported, if at all.
+    unknown_lints: Option<Level>,
+    /// The level at which malformed directives are reported, if at all.
+    invalid_directives: Option<Level>,
+    /// A pool of parsers available for reuse.
+    parsers: Mutex<Vec<Parser>>,
+}
+
+impl Linter {
+    /// Create a new [`Linter`] using the provided set of lints and
+    /// options.
+    ///
+    /// Lints configured at [`Level::Allow`] are skipped altogether. An
+    /// error is reported if any of the remaining lints' queries fails
+    /// to compile or has otherwise invalid properties.
+    pub fn new<I, L>(lints: I, mut opts: LintOpts) -> Result<Self>
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<Lint>,
+    {
+        // Explicitly configured levels take precedence over the
+        // dedicated options for matches reported by the linter itself.
+        let level = |name: &str, default| opts.levels.get(name).copied().or(default);
+        let unknown_lints = level(UNKNOWN_LINT, opts.unused_directives);
+        let invalid_directives = level(INVALID_DIRECTIVE, opts.unused_directives);
+        opts.parse_errors = level(PARSE_ERROR, opts.parse_errors);
+        opts.unused_directives = level(UNUSED_DIRECTIVE, opts.unused_directives);
+        opts.unjustified_directives = level(UNJUSTIFIED_DIRECTIVE, opts.unjustified_directives);
+
+        let mut known = SYNTHETIC_LINTS
+            .iter()
+            .map(ToString::to_string)
+            .chain(opts.synthetic_lints.iter().cloned())
+            .collect::<HashSet<_>>();
+        // With the exception of `parse-error`, we can't tell whether
+        // directives referring to synthetic lints suppressed anything,
+        // so never consider them unused.
+        let mut unchecked = known
+            .iter()
+            .filter(|name| *name != PARSE_ERROR)
+            .cloned()
+            .collect::<HashSet<_>>();
+        if matches!(opts.parse_errors, None | Some(Level::Allow)) {
+            let _inserted = unchecked.insert(PARSE_ERROR.to_string());
+        }
+
+        let lints = lints
+            .into_iter()
+            .filter_map(|lint| {
+                let lint = lint.as_ref();
+                let _inserted = known.insert(lint.name.clone());
+                let level = opts
+                    .levels
+                    .get(&lint.name)
+                    .copied()
+                    .unwrap_or_else(|| Level::default_for(&lint.name));
+                if level == Level::Allow {
+                    let _inserted = unchecked.insert(lint.name.clone());
+                }
+                (level != Level::Allow).then(|| (lint.clone(), level))
+            })
+            .collect();
+        let lints = CompiledLints::new(lints)?;
+
+        let slf = Self {
+            lints,
+            known,
+            unchecked,
+            opts,
+            unknown_lints,
+            invalid_directives,
+            parsers: Mutex::new(Vec::new()),
+        };
+        Ok(slf)
+    }
+
+    /// Retrieve the lints used by this [`Linter`].
+    pub fn lints(&self) -> impl ExactSizeIterator<Item = &Lint> + DoubleEndedIterator + Clone {
+        self.lints.lints.iter().map(|(lint, _level)| lint)
+    }
+
+    /// Retrieve the options used by this [`Linter`].
+    #[inline]
+    pub fn opts(&self) -> &LintOpts {
+        &self.opts
+    }
+
+    /// Check whether the linter reports problematic `bpflint:`
+    /// directives, which requires knowledge of all matches in a file.
+    pub(crate) fn checks_directives(&self) -> bool {
+        [
+            self.opts.unused_directives,
+            self.unknown_lints,
+            self.invalid_directives,
+        ]
+        .into_iter()
+        .any(|level| !matches!(level, None | Some(Level::Allow)))
+    }
+
+    /// Parse the provided source code, reusing a cached parser if one
+    /// is available.
+    ///
+    /// If `old_tree` is provided, it has to be edited to reflect the
+    /// changes made to the code and parsing happens incrementally.
+    pub(crate) fn parse(&self, code: &[u8], old_tree: Option<&Tree>) -> Result<Tree> {
+        let parser = self
+            .parsers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        let mut parser = if let Some(parser) = parser {
+            parser
+        } else {
+            let mut parser = Parser::new();
+            let () = parser
+                .set_language(&LANGUAGE.into())
+                .context("failed to load BPF C language parser")?;
+            parser
+        };
+
+        let tree = parser
+            .parse(code, old_tree)
+            .context("failed to parse provided source code");
+        let () = self
+            .parsers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(parser);
+        tree
+    }
+
+    /// Lint code using the lints and options this [`Linter`] was
+    /// created with.
+    ///
+    /// Matches are reported in source code order.
+    ///
+    /// - `code` is the source code in question, for example as read
+    ///   from a file
+    pub fn lint(&self, code: &[u8]) -> Result<Vec<LintMatch>> {
+        let tree = self.parse(code, None)?;
+        let directives = Directives::new(&tree, code);
+        let mut results = self.lint_tree(&tree, code, &directives, None)?;
+        let () = sort_matches(&mut results);
+        Ok(results)
+    }
+
+    /// Lint the already parsed `tree` of `code`, containing
+    /// `directives`, optionally considering only matches intersecting
+    /// with the provided byte range.
+    ///
+    /// Matches are reported in no particular order.
+    pub(crate) fn lint_tree(
+        &self,
+        tree: &Tree,
+        code: &[u8],
+        directives: &Directives,
+        bytes: Option<ops::Range<usize>>,
+    ) -> Result<Vec<LintMatch>> {
+        let mut results = lint_impl(
+            tree,
+            code,
+            directives,
+            &self.lints,
+            &self.opts,
+            bytes.clone(),
+        )?;
+
+        match self.opts.parse_errors {
+            None | Some(Level::Allow) => (),
+            Some(level) => {
+                let () = results.extend(parse_errors(tree, directives, level, bytes.as_ref()));
+            },
+        }
+
+        let in_range = |m: &LintMatch| {
+            bytes.as_ref().is_none_or(|bytes| {
+                m.range.bytes.start <= bytes.end && bytes.start <= m.range.bytes.end
+            })
+        };
+
+        // NB: Directives have to be checked last, after they had a
+        //     chance to suppress all other matches.
+        if self.checks_directives() {
+            let matches = directives
+                .check(
+                    &self.known,
+                    &self.unchecked,
+                    self.opts.unused_directives,
+                    self.unknown_lints,
+                    self.invalid_directives,
+                )
+                .into_iter()
+                .filter(in_range);
+            let () = results.extend(matches);
+        }
+
+        match self.opts.unjustified_directives {
+            None | Some(Level::Allow) => (),
+            Some(level) => {
+                let matches = directives.check_reasons(level).into_iter().filter(in_range);
+                let () = results.extend(matches);
+            },
+        }
+        Ok(results)
+    }
+
+    /// Retrieve the lint suppressions by means of `bpflint:` directives
+    /// contained in `code`, in source code order.
+    ///
+    /// - `code` is the source code in question, for example as read
+    ///   from a file
+    pub fn suppressions(&self, code: &[u8]) -> Result<Vec<Suppression>> {
+        let tree = self.parse(code, None)?;
+        let directives = Directives::new(&tree, code);
+        Ok(directives.suppressions())
+    }
+
+    /// Fix code by applying all available [`Suggestion`]s of lint
+    /// matches.
+    ///
+    /// If suggestions overlap, only the first one in source code order
+    /// is applied. After applying suggestions, the code is linted
+    /// again and remaining suggestions are applied until no more
+    /// progress is made or a maximum number of passes was reached. The
+    /// returned [`Fixed`] object contains the matches that are still
+    /// present in the fixed code.
+    ///
+    /// - `code` is the source code in question, for example as read
+    ///   from a file
+    pub fn fix(&self, code: &[u8]) -> Result<Fixed> {
+        let mut code = code.to_vec();
+        let mut fixed = Vec::new();
+
+        for _ in 0..MAX_FIX_PASSES {
+            let matches = self.lint(&code)?;
+            let (indices, suggestions) = matches
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, m)| Some((idx, m.suggestion.as_ref()?)))
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            let (new_code, applied) = apply_suggestions(&code, &suggestions);
+            if applied.is_empty() {
+                let fixed = Fixed {
+                    code,
+                    fixed,
+                    matches,
+                };
+                return Ok(fixed)
+            }
+
+            let () = fixed.extend(applied.into_iter().map(|idx| matches[indices[idx]].clone()));
+            code = new_code;
+        }
+
+        let matches = self.lint(&code)?;
+        let fixed = Fixed {
+            code,
+            fixed,
+            matches,
+        };
+        Ok(fixed)
+    }
+}
+
+impl Debug for Linter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let Self {
+            lints,
+            known: _,
+            unchecked: _,
+            opts,
+            unknown_lints: _,
+            invalid_directives: _,
+            parsers: _,
+        } = self;
+
+        f.debug_struct("Linter")
+            .field("lints", lints)
+            .field("opts", opts)
+            .finish_non_exhaustive()
+    }
+}
+
+
 /// Lint code using the provided set of lints.
 ///
 /// Matches are reported in source code order.
@@ -258,6 +954,9 @@ where
 /// Lint code using the provided set of lints with custom options.
 /// This function behaves the same as [`lint_custom`], but allows for
 /// additional configuration via a [`LintOpts`] object.
+///
+/// Note that lint queries are compiled on every invocation. When
+/// linting many source files, consider using a [`Linter`] instead.
 pub fn lint_custom_opts<'l, I, L>(
     code: &[u8],
     lints: I,
@@ -267,32 +966,8 @@ where
     I: IntoIterator<Item = L>,
     L: AsRef<Lint> + 'l,
 {
-    let mut parser = Parser::new();
-    let () = parser
-        .set_language(&LANGUAGE.into())
-        .context("failed to load BPF C language parser")?;
-    let tree = parser
-        .parse(code, None)
-        .context("failed to provided source code")?;
-    let mut results = Vec::new();
-    for lint in lints {
-        let matches = lint_impl(&tree, code, lint.as_ref(), lint_opts)?;
-        let () = results.extend(matches);
-    }
-
-    // Sort results to ensure more consistent reporting with ascending
-    // lines.
-    let () = results.sort_by(|match1, match2| {
-        // NB: We use an ad-hoc comparison rather than a proper
-        // `PartialOrd` impl for `Range`, because the latter is a bit
-        // harder to do correctly.
-        match1
-            .range
-            .start_point
-            .cmp(&match2.range.start_point)
-            .then_with(|| match1.range.end_point.cmp(&match2.range.end_point))
-    });
-    Ok(results)
+    let linter = Linter::new(lints, lint_opts.clone())?;
+    linter.lint(code)
 }
 
 /// Lint code using the default ([built-in][builtin_lints]) set of lints.
@@ -310,9 +985,14 @@ pub fn lint(code: &[u8]) -> Result<Vec<LintMatch>> {
 mod tests {
     use super::*;
 
+    use std::fs::write;
+
     use indoc::indoc;
 
+    use tempfile::TempDir;
+
     use crate::Point;
+    use crate::Scope;
 
 
     fn lint_foo() -> Lint {
@@ -397,6 +1077,9 @@ mod tests {
             lint_name,
             message,
             range,
+            min_kernel_version,
+            level,
+            suggestion,
         } = &matches[0];
         assert_eq!(lint_name, "probe-read");
         assert!(
@@ -406,6 +1089,9 @@ mod tests {
         assert_eq!(&code[range.bytes.clone()], "bpf_probe_read");
         assert_eq!(range.start_point, Point { row: 6, col: 4 });
         assert_eq!(range.end_point, Point { row: 6, col: 18 });
+        assert_eq!(*min_kernel_version, Some(Version(5, 5, 0)));
+        assert_eq!(*level, Level::Warn);
+        assert_eq!(*suggestion, None);
     }
 
     /// Check that reported matches are sorted by line number.
@@ -495,6 +1181,660 @@ mod tests {
         assert_eq!(matches.len(), 6, "{matches:?}");
     }
 
+    /// Check that the various directive forms are honored.
+    #[test]
+    fn lint_disabling_directives() {
+        let bar = Lint {
+            name: "bar".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#eq? @function "bar")
+                )
+            "# }
+            .to_string(),
+            message: "bar".to_string(),
+        };
+
+        let code = indoc! { r#"
+            /* bpflint: disable=foo,bar */
+            {
+                foo();
+                bar();
+            }
+            // bpflint: disable-next-line=bar
+            foo(); bar();
+            bar();
+
+            /* bpflint: disable=all */
+            void test_fn(void) {
+                foo();
+                /* bpflint: enable=foo */
+                {
+                    foo();
+                    bar();
+                }
+            }
+        "# };
+        let matches = lint_custom(code.as_bytes(), [lint_foo(), bar.clone()]).unwrap();
+        let matches = matches
+            .iter()
+            .map(|m| (m.lint_name.as_str(), m.range.start_point.row))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![("foo", 6), ("bar", 7), ("foo", 14)]);
+
+        let code = indoc! { r#"
+            // SPDX-License-Identifier: GPL-2.0
+            /* bpflint: disable-file=foo */
+
+            foo();
+            bar();
+            /* bpflint: enable=foo */
+            foo();
+            /* bpflint: disable-file=bar */
+            bar();
+        "# };
+        let matches = lint_custom(code.as_bytes(), [lint_foo(), bar]).unwrap();
+        let matches = matches
+            .iter()
+            .map(|m| (m.lint_name.as_str(), m.range.start_point.row))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![("bar", 4), ("foo", 6), ("bar", 8)]);
+    }
+
+    /// Check that unused directives and those referring to unknown
+    /// lints are reported when requested.
+    #[test]
+    fn directive_checking() {
+        let code = indoc! { r#"
+            /* bpflint: disable=foo */
+            foo();
+            /* bpflint: disable=foo,bar */
+            foo();
+            // bpflint: disable-next-line=foo_
+            bar();
+            /* bpflint: enable=foo */
+            bar();
+            /* bpflint: disable=all */
+            bar();
+        "# };
+
+        let matches = lint_custom(code.as_bytes(), [lint_foo()]).unwrap();
+        assert_eq!(matches.len(), 0, "{matches:?}");
+
+        let mut lint_opts = LintOpts {
+            unused_directives: Some(Level::Deny),
+            ..Default::default()
+        };
+        let bar = Lint {
+            name: "bar".to_string(),
+            code: "(identifier) @__bar".to_string(),
+            message: "bar".to_string(),
+        };
+        let matches =
+            lint_custom_opts(code.as_bytes(), [lint_foo(), bar.clone()], &lint_opts).unwrap();
+        let matches = matches
+            .iter()
+            .map(|m| {
+                (
+                    m.lint_name.as_str(),
+                    m.message.as_str(),
+                    m.range.start_point.row,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                (
+                    "unused-directive",
+                    "directive disables `bar`, but no match was suppressed",
+                    2
+                ),
+                ("unknown-lint", "directive refers to unknown lint `foo_`", 4),
+                (
+                    "unused-directive",
+                    "directive disables `all`, but no match was suppressed",
+                    8
+                ),
+            ]
+        );
+
+        // Directives referring to lints that are not checked are not
+        // considered unused.
+        let _prev = lint_opts.levels.insert("bar".to_string(), Level::Allow);
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo(), bar], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "unknown-lint");
+        assert_eq!(matches[1].lint_name, "unused-directive");
+        assert_eq!(matches[1].level, Level::Deny);
+
+        // Levels configured for the individual kinds of matches take
+        // precedence.
+        let lint_opts = LintOpts {
+            levels: HashMap::from([
+                ("unknown-lint".to_string(), Level::Warn),
+                ("unused-directive".to_string(), Level::Allow),
+            ]),
+            ..Default::default()
+        };
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert!(
+            matches
+                .iter()
+                .all(|m| m.lint_name == "unknown-lint" && m.level == Level::Warn)
+        );
+    }
+
+    /// Make sure that directives referring to lints reported by the
+    /// linter or the application are not reported as unknown.
+    #[test]
+    fn synthetic_lint_directives() {
+        let code = indoc! { r#"
+            /* bpflint: disable=unused-directive */
+            bar();
+            /* bpflint: disable=unjustified-directive,parse-error */
+            bar();
+            // bpflint: disable-next-line=bogus-file-extension
+            bar();
+        "# };
+
+        let mut lint_opts = LintOpts {
+            unused_directives: Some(Level::Warn),
+            ..Default::default()
+        };
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(
+            matches[0].message,
+            "directive refers to unknown lint `bogus-file-extension`"
+        );
+
+        // Suppression of parse errors is tracked, so unused directives
+        // disabling them are reported once they are checked.
+        lint_opts.parse_errors = Some(Level::Warn);
+        lint_opts.synthetic_lints = vec!["bogus-file-extension".to_string()];
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(
+            matches[0].message,
+            "directive disables `parse-error`, but no match was suppressed"
+        );
+    }
+
+    /// Check that malformed directives are reported when checking
+    /// directives.
+    #[test]
+    fn invalid_directive_checking() {
+        let code = indoc! { r#"
+            // bpflint: disabled=foo
+            foo();
+        "# };
+
+        let matches = lint_custom(code.as_bytes(), [lint_foo()]).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "foo");
+
+        let mut lint_opts = LintOpts {
+            unused_directives: Some(Level::Warn),
+            ..Default::default()
+        };
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "invalid-directive");
+        assert_eq!(matches[0].level, Level::Warn);
+        assert_eq!(matches[0].range.start_point.row, 0);
+        assert_eq!(matches[1].lint_name, "foo");
+
+        let _prev = lint_opts
+            .levels
+            .insert("invalid-directive".to_string(), Level::Allow);
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "foo");
+    }
+
+    /// Check that suppressions are reported along with their reasons
+    /// and that those without one are flagged when requested.
+    #[test]
+    fn suppression_reasons() {
+        let code = indoc! { r#"
+            /* bpflint: disable-file=bar -- generated code */
+            /* bpflint: disable=foo -- the callee is safe here */
+            foo();
+            // bpflint: disable-next-line=foo --
+            foo();
+            /* bpflint: enable=bar */
+            foo();
+        "# };
+
+        let linter = Linter::new([lint_foo()], LintOpts::default()).unwrap();
+        let suppressions = linter.suppressions(code.as_bytes()).unwrap();
+        let suppressions = suppressions
+            .iter()
+            .map(|s| {
+                (
+                    s.scope,
+                    s.lints.clone(),
+                    s.reason.as_deref(),
+                    s.range.start_point.row,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suppressions,
+            vec![
+                (
+                    Scope::File,
+                    vec!["bar".to_string()],
+                    Some("generated code"),
+                    0
+                ),
+                (
+                    Scope::Item,
+                    vec!["foo".to_string()],
+                    Some("the callee is safe here"),
+                    1
+                ),
+                (Scope::NextLine, vec!["foo".to_string()], None, 3),
+            ]
+        );
+
+        let lint_opts = LintOpts {
+            unjustified_directives: Some(Level::Warn),
+            ..Default::default()
+        };
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "unjustified-directive");
+        assert_eq!(matches[0].range.start_point.row, 3);
+        assert_eq!(matches[1].lint_name, "foo");
+    }
+
+    /// Check that a [`Linter`] can be used for linting multiple
+    /// pieces of code.
+    #[test]
+    fn linter_reuse() {
+        let linter = Linter::new([lint_foo()], LintOpts::default()).unwrap();
+        assert_eq!(linter.lints().len(), 1);
+
+        let code = indoc! { r#"
+            foo();
+            bar();
+        "# };
+        let matches = linter.lint(code.as_bytes()).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+
+        let code = indoc! { r#"
+            foo();
+            foo();
+        "# };
+        let matches = linter.lint(code.as_bytes()).unwrap();
+        assert_eq!(matches.len(), 2, "{matches:?}");
+    }
+
+    /// Make sure that invalid lints are rejected when creating a
+    /// [`Linter`].
+    #[test]
+    fn linter_invalid_lint() {
+        let lint = Lint {
+            name: "invalid".to_string(),
+            code: "(call_expression".to_string(),
+            message: "invalid".to_string(),
+        };
+        let err = Linter::new([lint], LintOpts::default()).unwrap_err();
+        assert_eq!(err.to_string(), "failed to compile query of lint `invalid`");
+
+        let lint = Lint {
+            name: "invalid".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#eq? @function "bar")
+                    (#set! "min_kernel_version" "5.x.0")
+                )
+            "# }
+            .to_string(),
+            message: "invalid".to_string(),
+        };
+        let err = Linter::new([lint], LintOpts::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lint `invalid` specifies invalid `min_kernel_version` property"
+        );
+
+        // Lints must not reference captures of other lints, even though
+        // their queries are combined.
+        let lint = Lint {
+            name: "invalid".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @bar (#eq? @bar "bar")
+                    (#set! "fix" "{{function}}")
+                )
+            "# }
+            .to_string(),
+            message: "invalid".to_string(),
+        };
+        let err = Linter::new([lint_foo(), lint], LintOpts::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lint `invalid` specifies invalid `fix` property"
+        );
+
+        let lint = Lint {
+            name: "invalid".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#unknown? @function)
+                )
+            "# }
+            .to_string(),
+            message: "invalid".to_string(),
+        };
+        let err = Linter::new([lint], LintOpts::default()).unwrap_err();
+        assert_eq!(err.to_string(), "lint `invalid` uses invalid predicate");
+    }
+
+    /// Make sure that checking lints using a single combined query
+    /// yields the same results as checking each lint on its own.
+    #[test]
+    fn combined_query_equivalence() {
+        let code = include_bytes!("../examples/task_longrun.bpf.c");
+        let lint_opts = LintOpts {
+            kernel_version: Some(Version(5, 10, 0)),
+            ..Default::default()
+        };
+
+        // Matches at the same location may be reported in different
+        // order, so compare their sorted textual representations.
+        let linter = Linter::new(builtin_lints(), lint_opts.clone()).unwrap();
+        let mut combined = linter
+            .lint(code)
+            .unwrap()
+            .iter()
+            .map(|m| format!("{m:?}"))
+            .collect::<Vec<_>>();
+        let () = combined.sort();
+        assert!(!combined.is_empty());
+
+        let mut separate = builtin_lints()
+            .flat_map(|lint| {
+                let linter = Linter::new([lint], lint_opts.clone()).unwrap();
+                linter.lint(code).unwrap()
+            })
+            .map(|m| format!("{m:?}"))
+            .collect::<Vec<_>>();
+        let () = separate.sort();
+        assert_eq!(combined, separate);
+    }
+
+    /// Check that lints can provide fix suggestions referencing
+    /// captures.
+    #[test]
+    fn fix_suggestions() {
+        let code = indoc! { r#"
+            foo(key_size);
+            foo();
+        "# };
+        let lint = Lint {
+            name: "foo".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#eq? @function "foo")
+                    arguments: (argument_list (identifier)? @__arg)
+                    (#set! "fix" "{{function}}_btf({{__arg%_size}})")
+                )
+            "# }
+            .to_string(),
+            message: "foo".to_string(),
+        };
+        let matches = lint_custom(code.as_bytes(), [lint]).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            matches[0].suggestion,
+            Some(Suggestion {
+                range: matches[0].range.clone(),
+                replacement: "foo_btf(key)".to_string(),
+            })
+        );
+        // The referenced capture is not part of the second match, so
+        // there is no suggestion.
+        assert_eq!(matches[1].suggestion, None);
+
+        let lint = Lint {
+            name: "invalid".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#eq? @function "foo")
+                    (#set! "fix" "{{args}}")
+                )
+            "# }
+            .to_string(),
+            message: "invalid".to_string(),
+        };
+        let err = Linter::new([lint], LintOpts::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lint `invalid` specifies invalid `fix` property"
+        );
+    }
+
+    /// Check that lints can be restricted to certain program types.
+    #[test]
+    fn program_type_restriction() {
+        let code = indoc! { r#"
+            SEC("xdp")
+            int handle_xdp(struct xdp_md *ctx) {
+                return foo();
+            }
+
+            SEC("tc/ingress")
+            int handle_tc(struct __sk_buff *skb) {
+                return foo();
+            }
+
+            static int bar(void) {
+                return foo();
+            }
+        "# };
+        let lint = |predicate: &str| Lint {
+            name: "foo".to_string(),
+            code: format!(
+                r#"(call_expression
+                    function: (identifier) @function
+                    (#eq? @function "foo")
+                    ({predicate} @function "xdp" "lsm")
+                )"#
+            ),
+            message: "foo".to_string(),
+        };
+
+        let matches = lint_custom(code.as_bytes(), [lint("#program-type?")]).unwrap();
+        let rows = matches
+            .iter()
+            .map(|m| m.range.start_point.row)
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![2]);
+
+        let matches = lint_custom(code.as_bytes(), [lint("#not-program-type?")]).unwrap();
+        let rows = matches
+            .iter()
+            .map(|m| m.range.start_point.row)
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![7, 11]);
+    }
+
+    /// Check that we can fix code using lint suggestions.
+    #[test]
+    fn linter_fix() {
+        let code = indoc! { r#"
+            struct {
+                __uint(type, BPF_MAP_TYPE_HASH);
+                __uint(key_size, sizeof(u32));
+                __uint(value_size, sizeof(struct event));
+            } events SEC(".maps");
+
+            SEC("tp_btf/sched_switch")
+            int handle__sched_switch(u64 *ctx)
+            {
+                struct task_struct *task = (struct task_struct *)bpf_get_current_task();
+                bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+                bpf_printk("foo");
+                return 0;
+            }
+        "# };
+        let expected = indoc! { r#"
+            struct {
+                __uint(type, BPF_MAP_TYPE_HASH);
+                __type(key, u32);
+                __type(value, struct event);
+            } events SEC(".maps");
+
+            SEC("tp_btf/sched_switch")
+            int handle__sched_switch(u64 *ctx)
+            {
+                struct task_struct *task = (struct task_struct *)bpf_get_current_task();
+                bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+                bpf_printk("foo");
+                return 0;
+            }
+        "# };
+        let lint = Lint {
+            name: "bpf-printk".to_string(),
+            code: indoc! { r#"
+                (call_expression
+                    function: (identifier) @function (#eq? @function "bpf_printk")
+                )
+            "# }
+            .to_string(),
+            message: "bpf_printk".to_string(),
+        };
+
+        let linter = Linter::new(builtin_lints().chain([lint]), LintOpts::default()).unwrap();
+        let Fixed {
+            code,
+            fixed,
+            matches,
+        } = linter.fix(code.as_bytes()).unwrap();
+        assert_eq!(String::from_utf8(code).unwrap(), expected);
+        assert_eq!(
+            fixed
+                .iter()
+                .map(|m| m.lint_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["untyped-map-member", "untyped-map-member"]
+        );
+        // Matches without suggestions remain.
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| m.lint_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["get-current-task", "probe-read", "bpf-printk"]
+        );
+        assert_eq!(matches[2].range.start_point, Point { row: 11, col: 4 });
+    }
+
+    /// Check that we can load lints from a directory.
+    #[test]
+    fn lint_loading() {
+        let dir = TempDir::new().unwrap();
+        let () = write(dir.path().join("foo.scm"), lint_foo().code).unwrap();
+        let () = write(dir.path().join("foo.txt"), "foo\n").unwrap();
+        let () = write(dir.path().join("README.md"), "ignored").unwrap();
+
+        let lints = load_lints_from_dir(dir.path()).unwrap();
+        assert_eq!(lints.len(), 1);
+        let Lint {
+            name,
+            code,
+            message,
+        } = &lints[0];
+        assert_eq!(name, "foo");
+        assert_eq!(code, &lint_foo().code);
+        assert_eq!(message, "foo");
+
+        let code = indoc! { r#"
+            foo();
+        "# };
+        let matches = lint_custom(code.as_bytes(), &lints).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+
+        // A lint lacking a message is an error.
+        let () = write(dir.path().join("bar.scm"), lint_foo().code).unwrap();
+        let err = load_lints_from_dir(dir.path()).unwrap_err();
+        assert!(
+            err.to_string().starts_with("failed to read lint message"),
+            "{err}"
+        );
+    }
+
+    /// Check that lint levels are honored.
+    #[test]
+    fn lint_levels() {
+        let code = indoc! { r#"
+            foo();
+        "# };
+
+        let mut lint_opts = LintOpts::default();
+        let _prev = lint_opts.levels.insert("foo".to_string(), Level::Deny);
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].level, Level::Deny);
+
+        let _prev = lint_opts.levels.insert("foo".to_string(), Level::Allow);
+        let linter = Linter::new([lint_foo()], lint_opts).unwrap();
+        assert_eq!(linter.lints().len(), 0);
+        let matches = linter.lint(code.as_bytes()).unwrap();
+        assert_eq!(matches.len(), 0, "{matches:?}");
+    }
+
+    /// Check that syntax errors are reported when requested.
+    #[test]
+    fn parse_error_reporting() {
+        let code = indoc! { r#"
+            int foo(void) {
+                int x = 1
+                return x;
+            }
+
+            int bar(void) {
+                @@@;
+                foo();
+            }
+
+            /* bpflint: disable=parse-error */
+            int baz(void) {
+                @@@;
+            }
+        "# };
+
+        let matches = lint_custom(code.as_bytes(), [lint_foo()]).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+
+        let lint_opts = LintOpts {
+            parse_errors: Some(Level::Deny),
+            ..Default::default()
+        };
+        let matches = lint_custom_opts(code.as_bytes(), [lint_foo()], &lint_opts).unwrap();
+        assert_eq!(matches.len(), 3, "{matches:?}");
+
+        assert_eq!(matches[0].lint_name, "parse-error");
+        assert_eq!(
+            matches[0].message,
+            "code could not be parsed (missing `;`), so lints may not detect issues in it"
+        );
+        assert_eq!(matches[0].range.bytes, 29..29);
+        assert_eq!(matches[0].level, Level::Deny);
+
+        assert_eq!(matches[1].lint_name, "parse-error");
+        assert_eq!(&code[matches[1].range.bytes.clone()], "@@@");
+        assert_eq!(matches[1].range.start_point, Point { row: 6, col: 4 });
+
+        // Lints still work on code that parsed fine.
+        assert_eq!(matches[2].lint_name, "foo");
+    }
+
     #[test]
     fn kernel_version_out_of_scope() {
         let lint_opts = LintOpts {
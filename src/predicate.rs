@@ -0,0 +1,384 @@
+//! Custom query predicates evaluated by the linter.
+//!
+//! Besides the predicates built into tree-sitter (`#eq?`, `#match?`,
+//! etc.), lint queries may use the following ones:
+//! - `(#unavailable-helper? @capture)` matches if the captured node
+//!   names a BPF helper or kfunc that is not available for the targeted
+//!   kernel version or for the type of the program it is used in, as
+//!   implied by the `SEC()` annotation of the enclosing function
+//! - `(#program-type? @capture "type" ...)` matches if the captured
+//!   node is part of a function whose `SEC()` annotation implies one of
+//!   the listed program types (e.g., `xdp` or `sched_cls`); its
+//!   negation `#not-program-type?` matches if that is not the case
+//! - `(#invalid-section? @capture)` matches if the captured string
+//!   literal is not a section name known to libbpf or if its arguments
+//!   are malformed; a corrected section name is suggested for the
+//!   capture, if a plausible one exists
+//! - `(#missing-license? @capture)` matches if the file does not declare
+//!   a license and the captured node is part of the first BPF program
+//!   it defines, so that the issue is reported only once per file
+//! - `(#gpl-only-helper? @capture)` matches if the captured node names
+//!   a BPF helper or kfunc that only programs with a GPL compatible
+//!   license may call, while the file declares a license that is not
+//! - `(#ringbuf-leak? @capture)` matches if the captured node is a
+//!   `return` statement or the closing brace of a function body that
+//!   may be reached with a ring buffer reservation that was neither
+//!   submitted nor discarded
+
+use std::cell::OnceCell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops;
+use std::str;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use tree_sitter::Node;
+use tree_sitter::QueryCapture;
+use tree_sitter::QueryPredicate;
+use tree_sitter::QueryPredicateArg;
+
+use crate::Version;
+use crate::helper;
+use crate::license::License;
+use crate::license::first_program;
+use crate::ringbuf::leaking_exits;
+use crate::section::ProgramType;
+use crate::section::Section;
+use crate::section::enclosing_section;
+use crate::section::validate;
+
+
+/// The context in which predicates are evaluated.
+#[derive(Debug)]
+pub(crate) struct Context<'tree, 'code> {
+    /// The source code being linted.
+    code: &'code [u8],
+    /// The root of the syntax tree of `code`.
+    root: Node<'tree>,
+    /// The minimum kernel version being targeted, if any.
+    kernel_version: Option<Version>,
+    /// The license declared by `code`, determined lazily.
+    license: OnceCell<License>,
+    /// The byte range of the first BPF program in `code`, if any,
+    /// determined lazily.
+    first_program: OnceCell<Option<ops::Range<usize>>>,
+    /// The exits of functions reachable with outstanding ring buffer
+    /// reservations, as determined by [`leaking_exits`], keyed by the
+    /// ID of the function.
+    ringbuf_leaks: RefCell<HashMap<usize, HashSet<usize>>>,
+}
+
+impl<'tree, 'code> Context<'tree, 'code> {
+    /// Create a [`Context`] for evaluating predicates on the syntax
+    /// tree rooted at `root` of `code`.
+    pub fn new(code: &'code [u8], root: Node<'tree>, kernel_version: Option<Version>) -> Self {
+        Self {
+            code,
+            root,
+            kernel_version,
+            license: OnceCell::new(),
+            first_program: OnceCell::new(),
+            ringbuf_leaks: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Retrieve the license declared by the code.
+    fn license(&self) -> &License {
+        self.license
+            .get_or_init(|| License::new(self.root, self.code))
+    }
+
+    /// Retrieve the byte range of the first BPF program in the code.
+    fn first_program(&self) -> Option<&ops::Range<usize>> {
+        self.first_program
+            .get_or_init(|| first_program(self.root).map(|program| program.byte_range()))
+            .as_ref()
+    }
+}
+
+
+/// Determine the type of the program that `node` is part of, if it can
+/// be inferred.
+fn program_type(node: Node<'_>, code: &[u8]) -> Option<ProgramType> {
+    enclosing_section(node, code)
+        .and_then(Section::parse)
+        .map(|section| section.program_type)
+}
+
+
+/// Check whether the helper or kfunc named by `node` is unavailable.
+fn is_unavailable_helper(node: Node<'_>, ctx: &Context<'_, '_>) -> bool {
+    let Ok(name) = str::from_utf8(&ctx.code[node.byte_range()]) else {
+        return false
+    };
+    let Some(helper) = helper::find(name) else {
+        return false
+    };
+
+    if let Some(kernel_version) = ctx.kernel_version {
+        if kernel_version < helper.min_kernel_version {
+            return true
+        }
+    }
+
+    match program_type(node, ctx.code) {
+        // Extension programs take on the type of the program they
+        // replace and `struct_ops` programs have helper sets specific
+        // to the structure they implement, so we can't tell.
+        Some(ProgramType::Ext | ProgramType::StructOps) | None => false,
+        Some(program_type) => !helper.is_allowed_in(program_type),
+    }
+}
+
+
+/// Check whether the string literal `node` contains an invalid section
+/// name, recording a suggested replacement in `suggestions`.
+fn is_invalid_section<'tree>(
+    node: Node<'tree>,
+    ctx: &Context<'_, '_>,
+    suggestions: &mut Vec<(Node<'tree>, String)>,
+) -> bool {
+    let Some(section) = str::from_utf8(&ctx.code[node.byte_range()])
+        .ok()
+        .and_then(|literal| literal.strip_prefix('"')?.strip_suffix('"'))
+    else {
+        return false
+    };
+
+    match validate(section) {
+        Ok(()) => false,
+        Err(suggestion) => {
+            if let Some(suggestion) = suggestion {
+                let () = suggestions.push((node, format!("\"{suggestion}\"")));
+            }
+            true
+        },
+    }
+}
+
+
+/// Check whether `node` is part of the first program of a file lacking
+/// a license.
+fn is_missing_license(node: Node<'_>, ctx: &Context<'_, '_>) -> bool {
+    if *ctx.license() != License::Missing {
+        return false
+    }
+
+    ctx.first_program()
+        .is_some_and(|program| program.start <= node.start_byte() && node.end_byte() <= program.end)
+}
+
+
+/// Check whether the helper or kfunc named by `node` may not be called
+/// because of the declared license.
+fn is_gpl_only_helper(node: Node<'_>, ctx: &Context<'_, '_>) -> bool {
+    let Ok(name) = str::from_utf8(&ctx.code[node.byte_range()]) else {
+        return false
+    };
+    helper::find(name).is_some_and(|helper| helper.gpl_only) && ctx.license().is_gpl_incompatible()
+}
+
+
+/// Check whether `node` is a function exit that may be reached with an
+/// outstanding ring buffer reservation.
+fn is_ringbuf_leak(node: Node<'_>, ctx: &Context<'_, '_>) -> bool {
+    let mut function = node;
+    while function.kind() != "function_definition" {
+        let Some(parent) = function.parent() else {
+            return false
+        };
+        function = parent;
+    }
+
+    ctx.ringbuf_leaks
+        .borrow_mut()
+        .entry(function.id())
+        .or_insert_with(|| leaking_exits(function, ctx.code))
+        .contains(&node.id())
+}
+
+
+/// A custom predicate used in a lint query.
+#[derive(Clone, Debug)]
+pub(crate) enum Predicate {
+    /// The `#unavailable-helper?` predicate.
+    UnavailableHelper {
+        /// The index of the capture to check.
+        capture: u32,
+    },
+    /// The `#program-type?` and `#not-program-type?` predicates.
+    ProgramType {
+        /// The index of the capture to check.
+        capture: u32,
+        /// The program types to check for.
+        program_types: Vec<ProgramType>,
+        /// Whether the predicate is negated.
+        negated: bool,
+    },
+    /// The `#invalid-section?` predicate.
+    InvalidSection {
+        /// The index of the capture to check.
+        capture: u32,
+    },
+    /// The `#missing-license?` predicate.
+    MissingLicense {
+        /// The index of the capture to check.
+        capture: u32,
+    },
+    /// The `#gpl-only-helper?` predicate.
+    GplOnlyHelper {
+        /// The index of the capture to check.
+        capture: u32,
+    },
+    /// The `#ringbuf-leak?` predicate.
+    RingbufLeak {
+        /// The index of the capture to check.
+        capture: u32,
+    },
+}
+
+impl Predicate {
+    /// Create a [`Predicate`] from its representation in a query.
+    pub fn new(predicate: &QueryPredicate) -> Result<Self> {
+        let QueryPredicate { operator, args } = predicate;
+        let single_capture = || match args.as_ref() {
+            [QueryPredicateArg::Capture(capture)] => Ok(*capture),
+            _ => bail!("predicate `#{operator}` expects a single capture argument"),
+        };
+
+        let slf = match operator.as_ref() {
+            "unavailable-helper?" => Self::UnavailableHelper {
+                capture: single_capture()?,
+            },
+            "invalid-section?" => Self::InvalidSection {
+                capture: single_capture()?,
+            },
+            "missing-license?" => Self::MissingLicense {
+                capture: single_capture()?,
+            },
+            "gpl-only-helper?" => Self::GplOnlyHelper {
+                capture: single_capture()?,
+            },
+            "ringbuf-leak?" => Self::RingbufLeak {
+                capture: single_capture()?,
+            },
+            "program-type?" | "not-program-type?" => match args.as_ref() {
+                [QueryPredicateArg::Capture(capture), program_types @ ..]
+                    if !program_types.is_empty() =>
+                {
+                    let program_types = program_types
+                        .iter()
+                        .map(|arg| match arg {
+                            QueryPredicateArg::String(program_type) => program_type.parse(),
+                            QueryPredicateArg::Capture(..) => {
+                                bail!("predicate `#{operator}` expects program type names")
+                            },
+                        })
+                        .collect::<Result<_>>()?;
+                    Self::ProgramType {
+                        capture: *capture,
+                        program_types,
+                        negated: operator.starts_with("not-"),
+                    }
+                },
+                _ => bail!(
+                    "predicate `#{operator}` expects a capture followed by at least one program type"
+                ),
+            },
+            _ => bail!("encountered unknown predicate `#{operator}`"),
+        };
+        Ok(slf)
+    }
+
+    /// Evaluate the predicate for the `captures` of a match.
+    ///
+    /// Predicates may suggest replacements for captured nodes, which
+    /// are added to `suggestions`.
+    pub fn eval<'tree>(
+        &self,
+        captures: &[QueryCapture<'tree>],
+        ctx: &Context<'_, '_>,
+        suggestions: &mut Vec<(Node<'tree>, String)>,
+    ) -> bool {
+        match self {
+            Self::UnavailableHelper { capture } => captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| is_unavailable_helper(c.node, ctx)),
+            Self::ProgramType {
+                capture,
+                program_types,
+                negated,
+            } => captures.iter().filter(|c| c.index == *capture).all(|c| {
+                let matches = program_type(c.node, ctx.code)
+                    .is_some_and(|program_type| program_types.contains(&program_type));
+                matches != *negated
+            }),
+            Self::InvalidSection { capture } => captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| is_invalid_section(c.node, ctx, suggestions)),
+            Self::MissingLicense { capture } => captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| is_missing_license(c.node, ctx)),
+            Self::GplOnlyHelper { capture } => captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| is_gpl_only_helper(c.node, ctx)),
+            Self::RingbufLeak { capture } => captures
+                .iter()
+                .filter(|c| c.index == *capture)
+                .any(|c| is_ringbuf_leak(c.node, ctx)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tree_sitter::Query;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    /// Check that invalid predicate usage is rejected.
+    #[test]
+    fn invalid_predicates() {
+        let query = Query::new(
+            &LANGUAGE.into(),
+            r#"
+            ((identifier) @a (#unavailable-helper? @a @a))
+            ((identifier) @b (#unavailable-helper? "foo"))
+            ((identifier) @c (#foo? @c))
+            ((identifier) @d (#program-type? @d))
+            ((identifier) @e (#not-program-type? @e "xdp" "tc"))
+            "#,
+        )
+        .unwrap();
+
+        let err = Predicate::new(&query.general_predicates(0)[0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "predicate `#unavailable-helper?` expects a single capture argument"
+        );
+        let err = Predicate::new(&query.general_predicates(1)[0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "predicate `#unavailable-helper?` expects a single capture argument"
+        );
+        let err = Predicate::new(&query.general_predicates(2)[0]).unwrap_err();
+        assert_eq!(err.to_string(), "encountered unknown predicate `#foo?`");
+        let err = Predicate::new(&query.general_predicates(3)[0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "predicate `#program-type?` expects a capture followed by at least one program type"
+        );
+        let err = Predicate::new(&query.general_predicates(4)[0]).unwrap_err();
+        assert_eq!(err.to_string(), "encountered unknown program type `tc`");
+    }
+}
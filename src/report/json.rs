@@ -0,0 +1,337 @@
+//! Functionality for reporting lint matches in machine readable JSON
+//! format.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use anyhow::Context as _;
+use anyhow::Result;
+
+use serde::Serialize;
+
+use crate::Level;
+use crate::LintMatch;
+use crate::Point;
+use crate::Range;
+use crate::Suggestion;
+
+
+#[derive(Debug, Serialize)]
+struct JsonPoint {
+    row: usize,
+    col: usize,
+}
+
+impl From<&Point> for JsonPoint {
+    fn from(other: &Point) -> Self {
+        let Point { row, col } = *other;
+        Self { row, col }
+    }
+}
+
+
+#[derive(Debug, Serialize)]
+struct JsonBytes {
+    start: usize,
+    end: usize,
+}
+
+
+#[derive(Debug, Serialize)]
+struct JsonRange {
+    bytes: JsonBytes,
+    start_point: JsonPoint,
+    end_point: JsonPoint,
+}
+
+impl From<&Range> for JsonRange {
+    fn from(other: &Range) -> Self {
+        let Range {
+            bytes,
+            start_point,
+            end_point,
+        } = other;
+        Self {
+            bytes: JsonBytes {
+                start: bytes.start,
+                end: bytes.end,
+            },
+            start_point: JsonPoint::from(start_point),
+            end_point: JsonPoint::from(end_point),
+        }
+    }
+}
+
+
+#[derive(Debug, Serialize)]
+struct JsonSuggestion<'m> {
+    range: JsonRange,
+    replacement: &'m str,
+}
+
+impl<'m> From<&'m Suggestion> for JsonSuggestion<'m> {
+    fn from(other: &'m Suggestion) -> Self {
+        let Suggestion { range, replacement } = other;
+        Self {
+            range: JsonRange::from(range),
+            replacement,
+        }
+    }
+}
+
+
+#[derive(Debug, Serialize)]
+struct JsonMatch<'m> {
+    lint_name: &'m str,
+    message: &'m str,
+    path: String,
+    range: JsonRange,
+    min_kernel_version: Option<String>,
+    level: &'static str,
+    suggestion: Option<JsonSuggestion<'m>>,
+}
+
+
+#[derive(Debug, Default, Serialize)]
+struct JsonSummary<'m> {
+    /// The number of files linted.
+    files: usize,
+    /// The total number of matches.
+    matches: usize,
+    /// The number of matches reported as warnings.
+    warnings: usize,
+    /// The number of matches reported as errors.
+    errors: usize,
+    /// The number of matches per lint.
+    lints: BTreeMap<&'m str, usize>,
+}
+
+
+#[derive(Debug, Serialize)]
+struct JsonReport<'m> {
+    matches: Vec<JsonMatch<'m>>,
+    summary: JsonSummary<'m>,
+}
+
+
+/// Report lint matches of a set of files as a single JSON document.
+///
+/// - `files` is an iterator over pairs of file paths and the lint
+///   matches found in the respective file; files without any matches
+///   should be included as well, as they are accounted for in the
+///   summary
+/// - `writer` is a reference to a [`io::Write`] to which to write the
+///   report
+///
+/// # Example
+/// ```json
+/// {
+///   "matches": [
+///     {
+///       "lint_name": "probe-read",
+///       "message": "bpf_probe_read() is deprecated and replaced by bpf_probe_user() and bpf_probe_kernel(); refer to bpf-helpers(7)",
+///       "path": "example.bpf.c",
+///       "range": {
+///         "bytes": {
+///           "start": 1337,
+///           "end": 1351
+///         },
+///         "start_point": {
+///           "row": 43,
+///           "col": 4
+///         },
+///         "end_point": {
+///           "row": 43,
+///           "col": 18
+///         }
+///       },
+///       "min_kernel_version": "5.5.0",
+///       "level": "warn",
+///       "suggestion": null
+///     }
+///   ],
+///   "summary": {
+///     "files": 1,
+///     "matches": 1,
+///     "warnings": 1,
+///     "errors": 0,
+///     "lints": {
+///       "probe-read": 1
+///     }
+///   }
+/// }
+/// ```
+pub fn report<'m, I, M>(files: I, writer: &mut dyn io::Write) -> Result<()>
+where
+    I: IntoIterator<Item = (&'m Path, M)>,
+    M: IntoIterator<Item = &'m LintMatch>,
+{
+    let mut matches = Vec::new();
+    let mut summary = JsonSummary::default();
+
+    for (path, file_matches) in files {
+        summary.files += 1;
+
+        for r#match in file_matches {
+            let LintMatch {
+                lint_name,
+                message,
+                range,
+                min_kernel_version,
+                level,
+                suggestion,
+            } = r#match;
+
+            summary.matches += 1;
+            match level {
+                Level::Allow => (),
+                Level::Warn => summary.warnings += 1,
+                Level::Deny => summary.errors += 1,
+            }
+            *summary.lints.entry(lint_name).or_default() += 1;
+
+            let r#match = JsonMatch {
+                lint_name,
+                message,
+                path: path.display().to_string(),
+                range: JsonRange::from(range),
+                min_kernel_version: min_kernel_version.map(|version| version.to_string()),
+                level: match level {
+                    Level::Allow => "allow",
+                    Level::Warn => "warn",
+                    Level::Deny => "deny",
+                },
+                suggestion: suggestion.as_ref().map(JsonSuggestion::from),
+            };
+            let () = matches.push(r#match);
+        }
+    }
+
+    let report = JsonReport { matches, summary };
+    let () = serde_json::to_writer_pretty(&mut *writer, &report)
+        .context("failed to write JSON report")?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Version;
+
+
+    /// Check that we can report matches of multiple files in JSON
+    /// format.
+    #[test]
+    fn multi_file_report() {
+        let range = Range {
+            bytes: 68..82,
+            start_point: Point { row: 2, col: 4 },
+            end_point: Point { row: 2, col: 18 },
+        };
+        let m = LintMatch {
+            lint_name: "probe-read".to_string(),
+            message: "bpf_probe_read() is \"deprecated\"".to_string(),
+            range: range.clone(),
+            min_kernel_version: Some(Version(5, 5, 0)),
+            level: Level::Deny,
+            suggestion: Some(Suggestion {
+                range,
+                replacement: "bpf_probe_read_kernel".to_string(),
+            }),
+        };
+        let matches = [m];
+        let files = [
+            (Path::new("foo.bpf.c"), matches.as_slice()),
+            (Path::new("bar.bpf.c"), [].as_slice()),
+        ];
+
+        let mut r = Vec::new();
+        let () = report(files, &mut r).unwrap();
+        let r = String::from_utf8(r).unwrap();
+        let expected = indoc! { r#"
+            {
+              "matches": [
+                {
+                  "lint_name": "probe-read",
+                  "message": "bpf_probe_read() is \"deprecated\"",
+                  "path": "foo.bpf.c",
+                  "range": {
+                    "bytes": {
+                      "start": 68,
+                      "end": 82
+                    },
+                    "start_point": {
+                      "row": 2,
+                      "col": 4
+                    },
+                    "end_point": {
+                      "row": 2,
+                      "col": 18
+                    }
+                  },
+                  "min_kernel_version": "5.5.0",
+                  "level": "deny",
+                  "suggestion": {
+                    "range": {
+                      "bytes": {
+                        "start": 68,
+                        "end": 82
+                      },
+                      "start_point": {
+                        "row": 2,
+                        "col": 4
+                      },
+                      "end_point": {
+                        "row": 2,
+                        "col": 18
+                      }
+                    },
+                    "replacement": "bpf_probe_read_kernel"
+                  }
+                }
+              ],
+              "summary": {
+                "files": 2,
+                "matches": 1,
+                "warnings": 0,
+                "errors": 1,
+                "lints": {
+                  "probe-read": 1
+                }
+              }
+            }
+        "# };
+        assert_eq!(r, expected);
+    }
+
+    /// Make sure that an empty set of files results in an empty
+    /// report.
+    #[test]
+    fn empty_report() {
+        let files: [(&Path, &[LintMatch]); 0] = [];
+        let mut r = Vec::new();
+        let () = report(files, &mut r).unwrap();
+        let r = String::from_utf8(r).unwrap();
+        let expected = indoc! { r#"
+            {
+              "matches": [],
+              "summary": {
+                "files": 0,
+                "matches": 0,
+                "warnings": 0,
+                "errors": 0,
+                "lints": {}
+              }
+            }
+        "# };
+        assert_eq!(r, expected);
+    }
+}
@@ -1,4 +1,7 @@
 #[cfg_attr(target_family = "wasm", expect(dead_code))]
 mod ansi_color;
 mod highlight;
+pub mod json;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sarif;
 pub mod terminal;
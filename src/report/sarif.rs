@@ -0,0 +1,544 @@
+//! Functionality for reporting lint matches in the [SARIF][sarif] 2.1.0
+//! format, as understood by many code scanning services.
+//!
+//! [sarif]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::absolute;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+
+use serde::Serialize;
+
+use url::Url;
+
+use crate::Level;
+use crate::Lint;
+use crate::LintMatch;
+use crate::Range;
+
+
+/// The URI of the SARIF 2.1.0 JSON schema.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// The SARIF version we emit.
+const SARIF_VERSION: &str = "2.1.0";
+/// The unit in which we report columns.
+const COLUMN_KIND: &str = "utf16CodeUnits";
+/// The base identifier of URIs relative to the working directory.
+const SRCROOT: &str = "%SRCROOT%";
+
+
+#[derive(Debug, Serialize)]
+struct Message<'m> {
+    text: &'m str,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor<'m> {
+    id: &'m str,
+    name: &'m str,
+    short_description: Message<'m>,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'m> {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor<'m>>,
+}
+
+
+#[derive(Debug, Serialize)]
+struct Tool<'m> {
+    driver: ToolComponent<'m>,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+
+/// A SARIF region. Note that SARIF lines and columns are one-based,
+/// while ours are zero-based.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: usize,
+    byte_length: usize,
+}
+
+impl Region {
+    /// Create a [`Region`] for `range` inside of `code`.
+    fn new(code: &[u8], range: &Range) -> Self {
+        let Range {
+            bytes,
+            start_point,
+            end_point,
+        } = range;
+        Self {
+            start_line: start_point.row + 1,
+            start_column: utf16_column(code, bytes.start) + 1,
+            end_line: end_point.row + 1,
+            // SARIF's end column refers to the first column *after*
+            // the region, same as ours.
+            end_column: utf16_column(code, bytes.end) + 1,
+            byte_offset: bytes.start,
+            byte_length: bytes.len(),
+        }
+    }
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'m> {
+    rule_id: &'m str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: &'static str,
+    message: Message<'m>,
+    locations: Vec<Location>,
+}
+
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'m> {
+    tool: Tool<'m>,
+    original_uri_base_ids: BTreeMap<&'static str, ArtifactLocation>,
+    column_kind: &'static str,
+    results: Vec<SarifResult<'m>>,
+}
+
+
+#[derive(Debug, Serialize)]
+struct Log<'m> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'m>>,
+}
+
+
+/// Determine the zero-based column of byte offset `byte` inside of
+/// `code`, in terms of UTF-16 code units, which is what SARIF uses by
+/// default.
+fn utf16_column(code: &[u8], byte: usize) -> usize {
+    let byte = byte.min(code.len());
+    let line_start = code[..byte]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    String::from_utf8_lossy(&code[line_start..byte])
+        .encode_utf16()
+        .count()
+}
+
+
+/// Convert a path into an artifact location as expected by SARIF.
+///
+/// Paths inside of the directory `root`, described by the `file` URI
+/// `root_uri`, are reported relative to [`SRCROOT`], all others as
+/// absolute `file` URIs.
+fn artifact_location(path: &Path, root: &Path, root_uri: &Url) -> Result<ArtifactLocation> {
+    let path =
+        absolute(path).with_context(|| format!("failed to make `{}` absolute", path.display()))?;
+    let uri = Url::from_file_path(&path)
+        .map_err(|()| anyhow!("failed to convert `{}` into a URI", path.display()))?;
+
+    let is_inside = path.strip_prefix(root).is_ok_and(|relative| {
+        relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    });
+    let relative = if is_inside {
+        root_uri.make_relative(&uri)
+    } else {
+        None
+    };
+    let location = match relative {
+        Some(relative) => ArtifactLocation {
+            uri: relative,
+            uri_base_id: Some(SRCROOT),
+        },
+        None => ArtifactLocation {
+            uri: uri.into(),
+            uri_base_id: None,
+        },
+    };
+    Ok(location)
+}
+
+
+/// Report lint matches of a set of files as a SARIF log.
+///
+/// - `lints` is the set of lints that was used for linting; it is used
+///   for populating the rules of the reporting tool
+/// - `files` is an iterator over triples of file paths, the source code
+///   contained in the respective file, and the lint matches found in it;
+///   paths inside the current working directory are reported relative
+///   to the `%SRCROOT%` base URI, which refers to it
+/// - `writer` is a reference to a [`io::Write`] to which to write the
+///   report
+pub fn report<'m, L, I, M>(lints: L, files: I, writer: &mut dyn io::Write) -> Result<()>
+where
+    L: IntoIterator<Item = &'m Lint>,
+    I: IntoIterator<Item = (&'m Path, &'m [u8], M)>,
+    M: IntoIterator<Item = &'m LintMatch>,
+{
+    let rules = lints
+        .into_iter()
+        .map(|lint| ReportingDescriptor {
+            id: &lint.name,
+            name: &lint.name,
+            short_description: Message {
+                text: &lint.message,
+            },
+        })
+        .collect::<Vec<_>>();
+    let rule_indices = rules
+        .iter()
+        .enumerate()
+        .map(|(idx, rule)| (rule.id, idx))
+        .collect::<HashMap<_, _>>();
+
+    let root = current_dir().context("failed to retrieve current working directory")?;
+    let root_uri = Url::from_directory_path(&root)
+        .map_err(|()| anyhow!("failed to convert `{}` into a URI", root.display()))?;
+
+    let mut results = Vec::new();
+    for (path, code, matches) in files {
+        let ArtifactLocation { uri, uri_base_id } = artifact_location(path, &root, &root_uri)?;
+        for r#match in matches {
+            let LintMatch {
+                lint_name,
+                message,
+                range,
+                min_kernel_version: _,
+                level,
+                suggestion: _,
+            } = r#match;
+
+            let location = Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation {
+                        uri: uri.clone(),
+                        uri_base_id,
+                    },
+                    // Empty ranges conceptually refer to the file as a
+                    // whole.
+                    region: (!range.bytes.is_empty()).then(|| Region::new(code, range)),
+                },
+            };
+            let result = SarifResult {
+                rule_id: lint_name,
+                rule_index: rule_indices.get(lint_name.as_str()).copied(),
+                level: match level {
+                    Level::Allow => "note",
+                    Level::Warn => "warning",
+                    Level::Deny => "error",
+                },
+                message: Message { text: message },
+                locations: vec![location],
+            };
+            let () = results.push(result);
+        }
+    }
+
+    let log = Log {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: ToolComponent {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            original_uri_base_ids: BTreeMap::from([(
+                SRCROOT,
+                ArtifactLocation {
+                    uri: root_uri.into(),
+                    uri_base_id: None,
+                },
+            )]),
+            column_kind: COLUMN_KIND,
+            results,
+        }],
+    };
+
+    let () =
+        serde_json::to_writer_pretty(&mut *writer, &log).context("failed to write SARIF report")?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    use std::env::current_dir;
+
+    use indoc::indoc;
+
+    use serde_json::Value;
+    use serde_json::json;
+
+    use crate::Point;
+    use crate::builtin_lints;
+
+
+    /// Check that lint matches are reported as expected in SARIF
+    /// format.
+    #[test]
+    fn multi_file_report() {
+        let lints = builtin_lints()
+            .filter(|lint| lint.name == "probe-read")
+            .collect::<Vec<_>>();
+        let matches = [
+            LintMatch {
+                lint_name: "bogus-file-extension".to_string(),
+                message: "by convention BPF C code should use the file extension '.bpf.c'"
+                    .to_string(),
+                range: Range {
+                    bytes: 0..0,
+                    start_point: Point::default(),
+                    end_point: Point::default(),
+                },
+                min_kernel_version: None,
+                level: Level::Warn,
+                suggestion: None,
+            },
+            LintMatch {
+                lint_name: "probe-read".to_string(),
+                message: "bpf_probe_read() is deprecated".to_string(),
+                range: Range {
+                    bytes: 43..57,
+                    start_point: Point { row: 2, col: 4 },
+                    end_point: Point { row: 2, col: 18 },
+                },
+                min_kernel_version: None,
+                level: Level::Deny,
+                suggestion: None,
+            },
+        ];
+        let code = indoc! { br#"
+            SEC("kprobe/foo")
+            int foo(void *ctx) {
+                bpf_probe_read(&x, 4, ptr);
+            }
+        "# };
+        let files = [
+            (Path::new("foo.c"), code.as_slice(), matches.as_slice()),
+            (Path::new("bar.bpf.c"), [].as_slice(), [].as_slice()),
+        ];
+        let root = Url::from_directory_path(current_dir().unwrap())
+            .unwrap()
+            .to_string();
+
+        let mut r = Vec::new();
+        let () = report(&lints, files, &mut r).unwrap();
+        let log = serde_json::from_slice::<Value>(&r).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "bpflint");
+        assert_eq!(run["columnKind"], "utf16CodeUnits");
+        assert_eq!(
+            run["originalUriBaseIds"],
+            json!({
+                "%SRCROOT%": {
+                    "uri": root,
+                },
+            })
+        );
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{
+                "id": "probe-read",
+                "name": "probe-read",
+                "shortDescription": {
+                    "text": lints[0].message,
+                },
+            }])
+        );
+
+        let expected = json!([
+            {
+                "ruleId": "bogus-file-extension",
+                "level": "warning",
+                "message": {
+                    "text": "by convention BPF C code should use the file extension '.bpf.c'",
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": "foo.c",
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                    },
+                }],
+            },
+            {
+                "ruleId": "probe-read",
+                "ruleIndex": 0,
+                "level": "error",
+                "message": {
+                    "text": "bpf_probe_read() is deprecated",
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": "foo.c",
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": {
+                            "startLine": 3,
+                            "startColumn": 5,
+                            "endLine": 3,
+                            "endColumn": 19,
+                            "byteOffset": 43,
+                            "byteLength": 14,
+                        },
+                    },
+                }],
+            },
+        ]);
+        assert_eq!(run["results"], expected);
+    }
+
+    /// Make sure that columns are reported in terms of UTF-16 code units
+    /// and that file URIs are properly escaped.
+    #[test]
+    fn non_ascii_report() {
+        // `ü` is one UTF-16 code unit in two bytes, `€` one in three,
+        // and `😀` two in four.
+        let code = "/* ü€😀 */ x = 1;\n";
+        let start = code.find('x').unwrap();
+        let matches = [LintMatch {
+            lint_name: "foo".to_string(),
+            message: "bar".to_string(),
+            range: Range {
+                bytes: start..start + 1,
+                start_point: Point { row: 0, col: start },
+                end_point: Point {
+                    row: 0,
+                    col: start + 1,
+                },
+            },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
+        }];
+        let files = [(
+            Path::new("dir with space/#1 100%.bpf.c"),
+            code.as_bytes(),
+            matches.as_slice(),
+        )];
+
+        let mut r = Vec::new();
+        let () = report([], files, &mut r).unwrap();
+        let log = serde_json::from_slice::<Value>(&r).unwrap();
+
+        let location = &log["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"],
+            json!({
+                "uri": "dir%20with%20space/%231%20100%25.bpf.c",
+                "uriBaseId": "%SRCROOT%",
+            })
+        );
+
+        let region = &location["region"];
+        assert_eq!(region["startColumn"], 12);
+        assert_eq!(region["endColumn"], 13);
+        assert_eq!(region["byteOffset"], 16);
+    }
+
+    /// Check that files outside of the working directory are reported
+    /// using absolute URIs.
+    #[test]
+    fn outside_working_directory() {
+        let dir = current_dir().unwrap();
+        let parent = dir.parent().unwrap();
+        let matches = [LintMatch {
+            lint_name: "foo".to_string(),
+            message: "bar".to_string(),
+            range: Range {
+                bytes: 0..0,
+                start_point: Point::default(),
+                end_point: Point::default(),
+            },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
+        }];
+        let absolute = parent.join("absolute.bpf.c");
+        let files = [
+            (absolute.as_path(), [].as_slice(), matches.as_slice()),
+            (
+                Path::new("../relative.bpf.c"),
+                [].as_slice(),
+                matches.as_slice(),
+            ),
+        ];
+
+        let mut r = Vec::new();
+        let () = report([], files, &mut r).unwrap();
+        let log = serde_json::from_slice::<Value>(&r).unwrap();
+
+        let results = &log["runs"][0]["results"];
+        let location = &results[0]["locations"][0]["physicalLocation"]["artifactLocation"];
+        let uri = Url::from_file_path(&absolute).unwrap().to_string();
+        assert_eq!(location, &json!({ "uri": uri }));
+
+        let location = &results[1]["locations"][0]["physicalLocation"]["artifactLocation"];
+        let uri = location["uri"].as_str().unwrap();
+        assert!(uri.starts_with("file:///"), "{uri}");
+        assert!(uri.ends_with("/relative.bpf.c"), "{uri}");
+        assert_eq!(location.get("uriBaseId"), None);
+    }
+}
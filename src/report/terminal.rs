@@ -7,9 +7,12 @@ use anyhow::Context as _;
 use anyhow::Error;
 use anyhow::Result;
 
+use crate::Level;
 use crate::LintMatch;
+use crate::Suggestion;
 use crate::lines::Lines;
 
+use super::highlight::Highlighter;
 use super::highlight::create_highlighter;
 
 
@@ -91,15 +94,23 @@ pub fn report_opts(
         lint_name,
         message,
         range,
+        min_kernel_version: _,
+        level,
+        suggestion,
     } = r#match;
 
     let highlighter = create_highlighter(opts.color)?;
 
     let (bold, warn, highlight, reset) = highlighter.format_strings();
 
+    let level = match level {
+        Level::Allow => "note",
+        Level::Warn => "warning",
+        Level::Deny => "error",
+    };
     writeln!(
         writer,
-        "{warn}warning{reset}{bold}: [{lint_name}] {message}{reset}"
+        "{warn}{level}{reset}{bold}: [{lint_name}] {message}{reset}"
     )?;
     let start_row = range.start_point.row;
     let end_row = range.end_point.row;
@@ -119,6 +130,9 @@ pub fn report_opts(
     let code_indent = if start_row == end_row { 0 } else { 3 };
 
     if range.bytes.is_empty() {
+        if let Some(suggestion) = suggestion {
+            let () = report_suggestion(suggestion, code, prefix_indent, &*highlighter, writer)?;
+        }
         return Ok(())
     }
 
@@ -213,6 +227,71 @@ pub fn report_opts(
         })?;
 
     writeln!(writer, "{prefix}")?;
+
+    if let Some(suggestion) = suggestion {
+        let () = report_suggestion(suggestion, code, prefix_indent, &*highlighter, writer)?;
+    }
+    Ok(())
+}
+
+
+/// Report a suggested fix in the form of a diff of the affected lines.
+fn report_suggestion(
+    suggestion: &Suggestion,
+    code: &[u8],
+    prefix_indent: usize,
+    highlighter: &dyn Highlighter,
+    writer: &mut dyn io::Write,
+) -> Result<()> {
+    let Suggestion { range, replacement } = suggestion;
+    let (bold, _warn, highlight, reset) = highlighter.format_strings();
+
+    // SANITY: It would be a tree-sitter bug the range does not
+    //         map to a valid code location.
+    let start = code[..range.bytes.start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let end = code[range.bytes.end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|idx| range.bytes.end + idx)
+        .unwrap_or(code.len());
+
+    let old = &code[start..end];
+    let new = [
+        &code[start..range.bytes.start],
+        replacement.as_bytes(),
+        &code[range.bytes.end..end],
+    ]
+    .concat();
+
+    let start_row = range.start_point.row;
+    let old_lines = old.split(|&b| b == b'\n').collect::<Vec<_>>();
+    let new_lines = new.split(|&b| b == b'\n').collect::<Vec<_>>();
+    let max_row = start_row + old_lines.len().max(new_lines.len()) - 1;
+    let prefix_indent = prefix_indent.max(max_row.to_string().len());
+    let prefix = format!("{highlight}{:prefix_indent$} |{reset} ", "");
+
+    writeln!(
+        writer,
+        "{highlight}help{reset}{bold}: apply the suggested fix{reset}"
+    )?;
+    writeln!(writer, "{prefix}")?;
+    for (marker, lines) in [('-', old_lines), ('+', new_lines)] {
+        for (idx, line) in lines.into_iter().enumerate() {
+            let row = start_row + idx;
+            let highlighted = highlighter
+                .highlight(line)
+                .context("failed to highlight source code line `{line}`")?;
+            writeln!(
+                writer,
+                "{highlight}{row:prefix_indent$} {marker}{reset} {highlighted}"
+            )?;
+        }
+    }
+    writeln!(writer, "{prefix}")?;
     Ok(())
 }
 
@@ -252,6 +331,9 @@ mod tests {
                 start_point: Point::default(),
                 end_point: Point::default(),
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report(&m, code.as_bytes(), Path::new("./no_bytes.c"), &mut r).unwrap();
@@ -263,6 +345,39 @@ mod tests {
         assert_eq!(r, expected);
     }
 
+    /// Check that matches at the deny level are reported as errors.
+    #[test]
+    fn error_reporting() {
+        let code = indoc! { r#"
+            bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+        "# };
+
+        let m = LintMatch {
+            lint_name: "probe-read".to_string(),
+            message: "bpf_probe_read() is deprecated".to_string(),
+            range: Range {
+                bytes: 0..14,
+                start_point: Point { row: 0, col: 0 },
+                end_point: Point { row: 0, col: 14 },
+            },
+            min_kernel_version: None,
+            level: Level::Deny,
+            suggestion: None,
+        };
+        let mut r = Vec::new();
+        let () = report(&m, code.as_bytes(), Path::new("<stdin>"), &mut r).unwrap();
+        let r = String::from_utf8(r).unwrap();
+        let expected = indoc! { r#"
+            error: [probe-read] bpf_probe_read() is deprecated
+              --> <stdin>:0:0
+              | 
+            0 | bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+              | ^^^^^^^^^^^^^^
+              | 
+        "# };
+        assert_eq!(r, expected);
+    }
+
     /// Make sure that multi-line matches are reported correctly.
     #[test]
     fn multi_line_report() {
@@ -285,6 +400,9 @@ mod tests {
                 start_point: Point { row: 2, col: 4 },
                 end_point: Point { row: 5, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report(&m, code.as_bytes(), Path::new("<stdin>"), &mut r).unwrap();
@@ -331,6 +449,9 @@ mod tests {
                 start_point: Point { row: 7, col: 4 },
                 end_point: Point { row: 10, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report(&m, code.as_bytes(), Path::new("<stdin>"), &mut r).unwrap();
@@ -367,6 +488,9 @@ mod tests {
                 start_point: Point { row: 0, col: 0 },
                 end_point: Point { row: 1, col: 0 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
 
         let mut r = Vec::new();
@@ -409,6 +533,9 @@ mod tests {
                 start_point: Point { row: 6, col: 4 },
                 end_point: Point { row: 6, col: 18 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report(&m, code.as_bytes(), Path::new("<stdin>"), &mut r).unwrap();
@@ -443,6 +570,9 @@ mod tests {
                 start_point: Point { row: 0, col: 4 },
                 end_point: Point { row: 0, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let opts = Opts {
@@ -488,6 +618,9 @@ mod tests {
                 start_point: Point { row: 0, col: 4 },
                 end_point: Point { row: 0, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report(&m, code.as_bytes(), Path::new("<stdin>"), &mut r).unwrap();
@@ -526,6 +659,9 @@ mod tests {
                 start_point: Point { row: 5, col: 4 },
                 end_point: Point { row: 5, col: 18 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
 
         let mut report_old = Vec::new();
@@ -566,6 +702,9 @@ mod tests {
                 start_point: Point { row: 5, col: 4 },
                 end_point: Point { row: 5, col: 18 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report_opts(
@@ -617,6 +756,9 @@ mod tests {
                 start_point: Point { row: 2, col: 4 },
                 end_point: Point { row: 5, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report_opts(
@@ -668,6 +810,9 @@ mod tests {
                 start_point: Point { row: 0, col: 4 },
                 end_point: Point { row: 0, col: 17 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report_opts(
@@ -715,6 +860,9 @@ mod tests {
                 start_point: Point { row: 3, col: 4 },
                 end_point: Point { row: 3, col: 18 },
             },
+            min_kernel_version: None,
+            level: Level::Warn,
+            suggestion: None,
         };
         let mut r = Vec::new();
         let () = report_opts(
@@ -0,0 +1,767 @@
+//! Control flow analysis of BPF ring buffer reservations.
+//!
+//! Every pointer returned by `bpf_ringbuf_reserve` has to be passed to
+//! either `bpf_ringbuf_submit` or `bpf_ringbuf_discard` on every path
+//! through a program, unless it is `NULL`. The verifier rejects programs
+//! that fail to do so with a rather cryptic "unreleased reference"
+//! error. The analysis in this module walks the statements of a
+//! function, tracking the variables holding outstanding reservations,
+//! and determines the exits that may be reached with any of them still
+//! outstanding.
+//!
+//! The analysis errs on the side of not reporting anything: a
+//! reservation passed to a function other than a BPF helper, stored
+//! somewhere else, or returned is considered released.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str;
+
+use tree_sitter::Node;
+
+use crate::helper;
+
+
+/// The helper reserving space in a ring buffer.
+const RESERVE: &str = "bpf_ringbuf_reserve";
+/// The helpers releasing a ring buffer reservation.
+const RELEASE: &[&str] = &["bpf_ringbuf_discard", "bpf_ringbuf_submit"];
+
+
+/// The names of the variables that may hold outstanding reservations at
+/// a given point, or `None` if that point is unreachable.
+type State<'code> = Option<BTreeSet<&'code str>>;
+
+
+/// Merge the states of two control flow paths joining.
+fn join<'code>(a: State<'code>, b: State<'code>) -> State<'code> {
+    match (a, b) {
+        (None, state) | (state, None) => state,
+        (Some(mut a), Some(b)) => {
+            let () = a.extend(b);
+            Some(a)
+        },
+    }
+}
+
+
+/// Remove any parentheses and casts around the expression `node`.
+fn strip(node: Node<'_>) -> Node<'_> {
+    match node.kind() {
+        "parenthesized_expression" => node.named_child(0).map(strip).unwrap_or(node),
+        "cast_expression" => node.child_by_field_name("value").map(strip).unwrap_or(node),
+        _ => node,
+    }
+}
+
+
+/// Retrieve the non-comment named children of `node`.
+fn children(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = node.walk();
+    let children = node
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() != "comment")
+        .collect();
+    children
+}
+
+
+/// A `switch` statement or loop that `break` and `continue` statements
+/// may refer to.
+struct Scope<'code> {
+    /// Whether the scope is a loop, as opposed to a `switch` statement.
+    is_loop: bool,
+    /// The states at `break` statements leaving the scope.
+    breaks: State<'code>,
+    /// The states at `continue` statements, if the scope is a loop.
+    continues: State<'code>,
+}
+
+impl Scope<'_> {
+    fn new(is_loop: bool) -> Self {
+        Self {
+            is_loop,
+            breaks: None,
+            continues: None,
+        }
+    }
+}
+
+
+/// The analysis of a single function.
+struct Analysis<'code> {
+    /// The source code being analyzed.
+    code: &'code [u8],
+    /// The states at `goto` statements, by label.
+    labels: HashMap<&'code str, State<'code>>,
+    /// The currently entered `switch` statements and loops, innermost
+    /// last.
+    scopes: Vec<Scope<'code>>,
+    /// The IDs of the exit nodes reachable with outstanding
+    /// reservations.
+    leaks: HashSet<usize>,
+}
+
+impl<'code> Analysis<'code> {
+    /// Retrieve the source code text of `node`.
+    fn text(&self, node: Node<'_>) -> &'code str {
+        str::from_utf8(&self.code[node.byte_range()]).unwrap_or_default()
+    }
+
+    /// Retrieve the name of the variable that expression `node` refers
+    /// to, if it is a plain variable reference.
+    fn variable(&self, node: Node<'_>) -> Option<&'code str> {
+        let node = strip(node);
+        (node.kind() == "identifier").then(|| self.text(node))
+    }
+
+    /// Retrieve the name of the variable declared by `declarator`.
+    fn declared(&self, declarator: Node<'_>) -> Option<&'code str> {
+        match declarator.kind() {
+            "identifier" => Some(self.text(declarator)),
+            _ => self.declared(declarator.child_by_field_name("declarator")?),
+        }
+    }
+
+    /// Check whether expression `node` reserves space in a ring buffer.
+    fn is_reservation(&self, node: Node<'_>) -> bool {
+        let node = strip(node);
+        node.kind() == "call_expression"
+            && node
+                .child_by_field_name("function")
+                .is_some_and(|function| self.text(function) == RESERVE)
+    }
+
+    /// Account for the assignment of the expression `value` to the
+    /// variable `name`, if any.
+    fn assign(&self, name: Option<&'code str>, value: Node<'_>, state: &mut BTreeSet<&'code str>) {
+        // A reservation copied elsewhere may be released through the
+        // copy, so stop tracking it.
+        if let Some(var) = self.variable(value) {
+            let _removed = state.remove(var);
+        }
+
+        if let Some(name) = name {
+            let _removed = state.remove(name);
+            if self.is_reservation(value) {
+                let _inserted = state.insert(name);
+            }
+        }
+    }
+
+    /// Evaluate the side effects of the expression `node`.
+    fn expr(&mut self, node: Node<'_>, state: &mut BTreeSet<&'code str>) {
+        match node.kind() {
+            "call_expression" => {
+                let args = node
+                    .child_by_field_name("arguments")
+                    .map(children)
+                    .unwrap_or_default();
+                for arg in &args {
+                    let () = self.expr(*arg, state);
+                }
+
+                let function = node
+                    .child_by_field_name("function")
+                    .map(|function| self.text(function))
+                    .unwrap_or_default();
+                if RELEASE.contains(&function) {
+                    if let Some(var) = args.first().and_then(|arg| self.variable(*arg)) {
+                        let _removed = state.remove(var);
+                    }
+                } else if function != RESERVE
+                    && !function.starts_with("__builtin_")
+                    && helper::find(function).is_none()
+                {
+                    // Functions other than helpers may release
+                    // reservations passed to them.
+                    for var in args.iter().filter_map(|arg| self.variable(*arg)) {
+                        let _removed = state.remove(var);
+                    }
+                }
+            },
+            "assignment_expression" => {
+                let (Some(left), Some(right)) = (
+                    node.child_by_field_name("left"),
+                    node.child_by_field_name("right"),
+                ) else {
+                    return
+                };
+                let () = self.expr(right, state);
+                let name = self.variable(left);
+                if name.is_none() {
+                    let () = self.expr(left, state);
+                }
+                let () = self.assign(name, right, state);
+            },
+            _ => {
+                for child in children(node) {
+                    let () = self.expr(child, state);
+                }
+            },
+        }
+    }
+
+    /// Evaluate the side effects of the optional expression `node` in
+    /// `state`.
+    fn eval(&mut self, node: Option<Node<'_>>, state: State<'code>) -> State<'code> {
+        let mut state = state?;
+        if let Some(node) = node {
+            let () = self.expr(node, &mut state);
+        }
+        Some(state)
+    }
+
+    /// Determine the variables known to be `NULL` if the condition
+    /// `cond` evaluates to `truth`.
+    ///
+    /// `None` is returned if the condition can never evaluate to
+    /// `truth`.
+    fn null_when(&self, cond: Node<'_>, truth: bool) -> Option<BTreeSet<&'code str>> {
+        let cond = strip(cond);
+        let none = BTreeSet::new();
+        match cond.kind() {
+            "identifier" => {
+                let vars = if truth {
+                    none
+                } else {
+                    BTreeSet::from([self.text(cond)])
+                };
+                Some(vars)
+            },
+            "number_literal" => {
+                let is_true = self.text(cond) != "0";
+                (is_true == truth).then_some(none)
+            },
+            "assignment_expression" => cond
+                .child_by_field_name("left")
+                .map(|left| self.null_when(left, truth))
+                .unwrap_or(Some(none)),
+            "unary_expression"
+                if cond.child_by_field_name("operator").map(|op| op.kind()) == Some("!") =>
+            {
+                cond.child_by_field_name("argument")
+                    .map(|arg| self.null_when(arg, !truth))
+                    .unwrap_or(Some(none))
+            },
+            "binary_expression" => {
+                let (Some(left), Some(op), Some(right)) = (
+                    cond.child_by_field_name("left"),
+                    cond.child_by_field_name("operator"),
+                    cond.child_by_field_name("right"),
+                ) else {
+                    return Some(none)
+                };
+
+                let is_null = |node: Node<'_>| {
+                    let node = strip(node);
+                    node.kind() == "null"
+                        || (node.kind() == "number_literal" && self.text(node) == "0")
+                };
+                // The value of an assignment is that of the variable
+                // assigned to.
+                let operand = |node: Node<'_>| {
+                    let node = strip(node);
+                    match node.kind() {
+                        "assignment_expression" => self.variable(node.child_by_field_name("left")?),
+                        _ => self.variable(node),
+                    }
+                };
+                let compared = if is_null(right) {
+                    operand(left)
+                } else if is_null(left) {
+                    operand(right)
+                } else {
+                    None
+                };
+
+                match (op.kind(), truth) {
+                    ("==", _) | ("!=", _) => {
+                        let vars = match compared {
+                            Some(var) if (op.kind() == "==") == truth => BTreeSet::from([var]),
+                            _ => none,
+                        };
+                        Some(vars)
+                    },
+                    // Both operands have to hold the respective truth
+                    // value.
+                    ("&&", true) | ("||", false) => {
+                        let mut vars = self.null_when(left, truth)?;
+                        let () = vars.extend(self.null_when(right, truth)?);
+                        Some(vars)
+                    },
+                    // Either operand holds the respective truth value.
+                    ("&&", false) | ("||", true) => {
+                        match (self.null_when(left, truth), self.null_when(right, truth)) {
+                            (None, vars) | (vars, None) => vars,
+                            (Some(left), Some(right)) => {
+                                Some(left.intersection(&right).copied().collect())
+                            },
+                        }
+                    },
+                    _ => Some(none),
+                }
+            },
+            _ => Some(none),
+        }
+    }
+
+    /// Evaluate the optional condition `cond`, returning the states for
+    /// it evaluating to true and to false, respectively.
+    fn branch(
+        &mut self,
+        cond: Option<Node<'_>>,
+        state: State<'code>,
+    ) -> (State<'code>, State<'code>) {
+        let Some(state) = self.eval(cond, state) else {
+            return (None, None)
+        };
+        let Some(cond) = cond else {
+            return (Some(state), None)
+        };
+
+        let refine = |vars: Option<BTreeSet<&'code str>>| {
+            vars.map(|vars| state.difference(&vars).copied().collect::<BTreeSet<_>>())
+        };
+        (
+            refine(self.null_when(cond, true)),
+            refine(self.null_when(cond, false)),
+        )
+    }
+
+    /// Analyze a loop, returning the state after it.
+    ///
+    /// For `do` loops, `test_first` is `false`.
+    fn r#loop(
+        &mut self,
+        entry: State<'code>,
+        cond: Option<Node<'_>>,
+        update: Option<Node<'_>>,
+        body: Node<'_>,
+        test_first: bool,
+    ) -> State<'code> {
+        // Iterate until the state at the loop head reaches a fixed
+        // point. It can only ever grow, so that is guaranteed.
+        let mut head = entry.clone();
+        loop {
+            let mut state = head.clone();
+            let mut exit = None;
+            if test_first {
+                (state, exit) = self.branch(cond, state);
+            }
+
+            let () = self.scopes.push(Scope::new(true));
+            state = self.stmt(body, state);
+            // SANITY: We pushed a scope above and any nested ones have
+            //         been popped already.
+            let scope = self.scopes.pop().unwrap();
+
+            state = join(state, scope.continues);
+            state = self.eval(update, state);
+            if !test_first {
+                (state, exit) = self.branch(cond, state);
+            }
+
+            let next = join(entry.clone(), state);
+            if next == head {
+                break join(exit, scope.breaks)
+            }
+            head = next;
+        }
+    }
+
+    /// Analyze the statement `node`, returning the state after it.
+    fn stmt(&mut self, node: Node<'_>, state: State<'code>) -> State<'code> {
+        match node.kind() {
+            "compound_statement" => children(node)
+                .into_iter()
+                .fold(state, |state, child| self.stmt(child, state)),
+            "declaration" => {
+                let mut state = state?;
+                for declarator in children(node) {
+                    if declarator.kind() != "init_declarator" {
+                        continue
+                    }
+                    let Some(value) = declarator.child_by_field_name("value") else {
+                        continue
+                    };
+                    let () = self.expr(value, &mut state);
+                    let name = declarator
+                        .child_by_field_name("declarator")
+                        .and_then(|declarator| self.declared(declarator));
+                    let () = self.assign(name, value, &mut state);
+                }
+                Some(state)
+            },
+            "if_statement" => {
+                let cond = node.child_by_field_name("condition");
+                let (then, otherwise) = self.branch(cond, state);
+                let then = match node.child_by_field_name("consequence") {
+                    Some(consequence) => self.stmt(consequence, then),
+                    None => then,
+                };
+                let otherwise = match node
+                    .child_by_field_name("alternative")
+                    .and_then(|alternative| children(alternative).into_iter().next())
+                {
+                    Some(alternative) => self.stmt(alternative, otherwise),
+                    None => otherwise,
+                };
+                join(then, otherwise)
+            },
+            "while_statement" | "for_statement" | "do_statement" => {
+                let Some(body) = node.child_by_field_name("body") else {
+                    return state
+                };
+                let state = match node.child_by_field_name("initializer") {
+                    Some(init) if init.kind() == "declaration" => self.stmt(init, state),
+                    init => self.eval(init, state),
+                };
+                self.r#loop(
+                    state,
+                    node.child_by_field_name("condition"),
+                    node.child_by_field_name("update"),
+                    body,
+                    node.kind() != "do_statement",
+                )
+            },
+            "switch_statement" => {
+                let Some(body) = node.child_by_field_name("body") else {
+                    return state
+                };
+                let state = self.eval(node.child_by_field_name("condition"), state);
+
+                let () = self.scopes.push(Scope::new(false));
+                let mut current = None;
+                let mut has_default = false;
+                for child in children(body) {
+                    if child.kind() != "case_statement" {
+                        current = self.stmt(child, current);
+                        continue
+                    }
+
+                    let value = child.child_by_field_name("value");
+                    has_default |= value.is_none();
+                    current = join(current, state.clone());
+                    for stmt in children(child) {
+                        if Some(stmt) != value {
+                            current = self.stmt(stmt, current);
+                        }
+                    }
+                }
+                // SANITY: We pushed a scope above and any nested ones have
+                //         been popped already.
+                let scope = self.scopes.pop().unwrap();
+
+                let exit = join(current, scope.breaks);
+                if has_default { exit } else { join(exit, state) }
+            },
+            "break_statement" => {
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.breaks = join(scope.breaks.take(), state);
+                }
+                None
+            },
+            "continue_statement" => {
+                if let Some(scope) = self.scopes.iter_mut().rev().find(|scope| scope.is_loop) {
+                    scope.continues = join(scope.continues.take(), state);
+                }
+                None
+            },
+            "goto_statement" => {
+                if let Some(label) = node.child_by_field_name("label") {
+                    let label = self.text(label);
+                    let entry = self.labels.entry(label).or_default();
+                    *entry = join(entry.take(), state);
+                }
+                None
+            },
+            "labeled_statement" => {
+                let label = node.child_by_field_name("label");
+                let state = match label {
+                    Some(label) => {
+                        join(state, self.labels.get(self.text(label)).cloned().flatten())
+                    },
+                    None => state,
+                };
+                children(node)
+                    .into_iter()
+                    .filter(|child| Some(*child) != label)
+                    .fold(state, |state, child| self.stmt(child, state))
+            },
+            "return_statement" => {
+                let mut state = state?;
+                if let Some(value) = children(node).into_iter().next() {
+                    let () = self.expr(value, &mut state);
+                    // A returned reservation is the caller's
+                    // responsibility.
+                    if let Some(var) = self.variable(value) {
+                        let _removed = state.remove(var);
+                    }
+                }
+
+                if !state.is_empty() {
+                    let _inserted = self.leaks.insert(node.id());
+                }
+                None
+            },
+            _ => {
+                let mut state = state?;
+                let () = self.expr(node, &mut state);
+                Some(state)
+            },
+        }
+    }
+}
+
+
+/// Determine the exits of the function `function` that may be reached
+/// with a ring buffer reservation that was neither submitted nor
+/// discarded.
+///
+/// Exits are `return` statements and the closing brace of the
+/// function's body. They are reported by their node IDs.
+pub(crate) fn leaking_exits(function: Node<'_>, code: &[u8]) -> HashSet<usize> {
+    let Some(body) = function.child_by_field_name("body") else {
+        return HashSet::new()
+    };
+
+    // Bail out early for the common case of functions not reserving
+    // anything. Our results would be unreliable for functions that
+    // could not be parsed properly.
+    if body.has_error()
+        || !code[body.byte_range()]
+            .windows(RESERVE.len())
+            .any(|window| window == RESERVE.as_bytes())
+    {
+        return HashSet::new()
+    }
+
+    let mut analysis = Analysis {
+        code,
+        labels: HashMap::new(),
+        scopes: Vec::new(),
+        leaks: HashSet::new(),
+    };
+
+    // `goto` statements may jump backwards, so iterate until the states
+    // at all labels reached a fixed point.
+    loop {
+        let labels = analysis.labels.clone();
+        let state = analysis.stmt(body, Some(BTreeSet::new()));
+        if analysis.labels != labels {
+            continue
+        }
+
+        if state.is_some_and(|state| !state.is_empty()) {
+            // SANITY: The body has no errors and so it ends with a
+            //         closing brace.
+            let brace = body.child(body.child_count() - 1).unwrap();
+            let _inserted = analysis.leaks.insert(brace.id());
+        }
+        break analysis.leaks
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tree_sitter::Parser;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    /// Determine the rows of the leaking exits of the single function
+    /// defined in `code`.
+    fn leaks(code: &str) -> Vec<usize> {
+        let mut parser = Parser::new();
+        let () = parser.set_language(&LANGUAGE.into()).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let root = tree.root_node();
+        let function = root.named_child(root.named_child_count() - 1).unwrap();
+        assert_eq!(function.kind(), "function_definition");
+
+        let exits = leaking_exits(function, code.as_bytes());
+        let mut rows = Vec::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if exits.contains(&node.id()) {
+                let () = rows.push(node.start_position().row);
+            }
+            let mut cursor = node.walk();
+            let () = stack.extend(node.children(&mut cursor));
+        }
+        let () = rows.sort();
+        rows
+    }
+
+
+    /// Check that reservations released on all paths are accepted.
+    #[test]
+    fn released_reservations() {
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (!e)
+        return 0;
+    if (ctx == NULL) {
+        bpf_ringbuf_discard(e, 0);
+        return 1;
+    }
+    bpf_ringbuf_submit(e, 0);
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), Vec::<usize>::new());
+
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *e;
+    if ((e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0)) != NULL)
+        bpf_ringbuf_submit(e, 0);
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), Vec::<usize>::new());
+
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (!e)
+        goto out;
+    if (fill(ctx))
+        goto discard;
+    bpf_ringbuf_submit(e, 0);
+    goto out;
+discard:
+    bpf_ringbuf_discard(e, 0);
+out:
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), Vec::<usize>::new());
+
+        // Reservations passed to other functions or returned are not
+        // tracked further.
+        let code = r#"
+struct event *prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (ctx)
+        return e;
+    send(e);
+}
+"#;
+        assert_eq!(leaks(code), Vec::<usize>::new());
+    }
+
+    /// Check that exits with outstanding reservations are reported.
+    #[test]
+    fn leaked_reservations() {
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (!e)
+        return 0;
+    if (bpf_get_current_pid_tgid() == 0)
+        return 0;
+    bpf_ringbuf_submit(e, 0);
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), vec![7]);
+
+        let code = r#"
+void prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (e)
+        bpf_probe_read_kernel(e, sizeof(*e), ctx);
+}
+"#;
+        assert_eq!(leaks(code), vec![6]);
+
+        let code = r#"
+int prog(void *ctx)
+{
+    for (int i = 0; i < 4; i++) {
+        struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+        if (!e)
+            break;
+        if (i == 2)
+            continue;
+        bpf_ringbuf_submit(e, 0);
+    }
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), vec![11]);
+
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *e = bpf_ringbuf_reserve(&rb, sizeof(*e), 0);
+    if (!e)
+        return 0;
+    switch (*(int *)ctx) {
+    case 0:
+        bpf_ringbuf_discard(e, 0);
+        break;
+    case 1:
+        bpf_ringbuf_submit(e, 0);
+        return 1;
+    }
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), vec![14]);
+    }
+
+    /// Make sure that conditions are taken into account when
+    /// determining which paths are feasible.
+    #[test]
+    fn null_checks() {
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *a = bpf_ringbuf_reserve(&rb, sizeof(*a), 0);
+    struct event *b = bpf_ringbuf_reserve(&rb, sizeof(*b), 0);
+    if (!a || NULL == b) {
+        if (a)
+            bpf_ringbuf_discard(a, 0);
+        if (b != 0)
+            bpf_ringbuf_discard(b, 0);
+        return 0;
+    }
+    while (1) {
+        bpf_ringbuf_submit(a, 0);
+        bpf_ringbuf_submit(b, 0);
+        return 0;
+    }
+}
+"#;
+        assert_eq!(leaks(code), Vec::<usize>::new());
+
+        let code = r#"
+int prog(void *ctx)
+{
+    struct event *a = bpf_ringbuf_reserve(&rb, sizeof(*a), 0);
+    struct event *b = bpf_ringbuf_reserve(&rb, sizeof(*b), 0);
+    if (!a && !b)
+        return 0;
+    if (!a || !b)
+        return 1;
+    bpf_ringbuf_submit(a, 0);
+    bpf_ringbuf_submit(b, 0);
+    return 0;
+}
+"#;
+        assert_eq!(leaks(code), vec![8]);
+    }
+}
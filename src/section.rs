@@ -0,0 +1,738 @@
+//! Interpretation of `SEC()` section names of BPF programs.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::str;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use tree_sitter::Node;
+
+
+/// A BPF program type, as known to the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ProgramType {
+    /// A socket filter program.
+    SocketFilter,
+    /// A kprobe, uprobe, or USDT program.
+    Kprobe,
+    /// A traffic control classifier program.
+    SchedCls,
+    /// A traffic control action program.
+    SchedAct,
+    /// A tracepoint program.
+    Tracepoint,
+    /// An XDP program.
+    Xdp,
+    /// A perf event program.
+    PerfEvent,
+    /// A cgroup socket buffer program.
+    CgroupSkb,
+    /// A cgroup socket program.
+    CgroupSock,
+    /// A lightweight tunnel ingress program.
+    LwtIn,
+    /// A lightweight tunnel egress program.
+    LwtOut,
+    /// A lightweight tunnel transmit program.
+    LwtXmit,
+    /// A socket operations program.
+    SockOps,
+    /// A socket map socket buffer program.
+    SkSkb,
+    /// A cgroup device access program.
+    CgroupDevice,
+    /// A socket map message program.
+    SkMsg,
+    /// A raw tracepoint program.
+    RawTracepoint,
+    /// A cgroup socket address program.
+    CgroupSockAddr,
+    /// A lightweight tunnel seg6local program.
+    LwtSeg6local,
+    /// An infrared remote control decoder program.
+    LircMode2,
+    /// A socket reuseport selection program.
+    SkReuseport,
+    /// A flow dissector program.
+    FlowDissector,
+    /// A cgroup sysctl access program.
+    CgroupSysctl,
+    /// A writable raw tracepoint program.
+    RawTracepointWritable,
+    /// A cgroup socket option program.
+    CgroupSockopt,
+    /// A BTF enabled tracing program, e.g., fentry, fexit, or an iterator.
+    Tracing,
+    /// A program implementing a kernel structure of operations.
+    StructOps,
+    /// An extension program replacing another BPF function.
+    Ext,
+    /// An LSM program.
+    Lsm,
+    /// A socket lookup program.
+    SkLookup,
+    /// A program runnable through the `bpf` system call.
+    Syscall,
+    /// A netfilter program.
+    Netfilter,
+}
+
+impl ProgramType {
+    /// Retrieve the name of the program type, as used by libbpf and
+    /// `bpftool`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SocketFilter => "socket_filter",
+            Self::Kprobe => "kprobe",
+            Self::SchedCls => "sched_cls",
+            Self::SchedAct => "sched_act",
+            Self::Tracepoint => "tracepoint",
+            Self::Xdp => "xdp",
+            Self::PerfEvent => "perf_event",
+            Self::CgroupSkb => "cgroup_skb",
+            Self::CgroupSock => "cgroup_sock",
+            Self::LwtIn => "lwt_in",
+            Self::LwtOut => "lwt_out",
+            Self::LwtXmit => "lwt_xmit",
+            Self::SockOps => "sock_ops",
+            Self::SkSkb => "sk_skb",
+            Self::CgroupDevice => "cgroup_device",
+            Self::SkMsg => "sk_msg",
+            Self::RawTracepoint => "raw_tracepoint",
+            Self::CgroupSockAddr => "cgroup_sock_addr",
+            Self::LwtSeg6local => "lwt_seg6local",
+            Self::LircMode2 => "lirc_mode2",
+            Self::SkReuseport => "sk_reuseport",
+            Self::FlowDissector => "flow_dissector",
+            Self::CgroupSysctl => "cgroup_sysctl",
+            Self::RawTracepointWritable => "raw_tracepoint_writable",
+            Self::CgroupSockopt => "cgroup_sockopt",
+            Self::Tracing => "tracing",
+            Self::StructOps => "struct_ops",
+            Self::Ext => "ext",
+            Self::Lsm => "lsm",
+            Self::SkLookup => "sk_lookup",
+            Self::Syscall => "syscall",
+            Self::Netfilter => "netfilter",
+        }
+    }
+}
+
+impl Display for ProgramType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProgramType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let program_type = match s {
+            "socket_filter" => Self::SocketFilter,
+            "kprobe" => Self::Kprobe,
+            "sched_cls" => Self::SchedCls,
+            "sched_act" => Self::SchedAct,
+            "tracepoint" => Self::Tracepoint,
+            "xdp" => Self::Xdp,
+            "perf_event" => Self::PerfEvent,
+            "cgroup_skb" => Self::CgroupSkb,
+            "cgroup_sock" => Self::CgroupSock,
+            "lwt_in" => Self::LwtIn,
+            "lwt_out" => Self::LwtOut,
+            "lwt_xmit" => Self::LwtXmit,
+            "sock_ops" => Self::SockOps,
+            "sk_skb" => Self::SkSkb,
+            "cgroup_device" => Self::CgroupDevice,
+            "sk_msg" => Self::SkMsg,
+            "raw_tracepoint" => Self::RawTracepoint,
+            "cgroup_sock_addr" => Self::CgroupSockAddr,
+            "lwt_seg6local" => Self::LwtSeg6local,
+            "lirc_mode2" => Self::LircMode2,
+            "sk_reuseport" => Self::SkReuseport,
+            "flow_dissector" => Self::FlowDissector,
+            "cgroup_sysctl" => Self::CgroupSysctl,
+            "raw_tracepoint_writable" => Self::RawTracepointWritable,
+            "cgroup_sockopt" => Self::CgroupSockopt,
+            "tracing" => Self::Tracing,
+            "struct_ops" => Self::StructOps,
+            "ext" => Self::Ext,
+            "lsm" => Self::Lsm,
+            "sk_lookup" => Self::SkLookup,
+            "syscall" => Self::Syscall,
+            "netfilter" => Self::Netfilter,
+            _ => bail!("encountered unknown program type `{s}`"),
+        };
+        Ok(program_type)
+    }
+}
+
+
+/// The shape of the arguments following a section's kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Args {
+    /// The section has to match exactly and takes no arguments.
+    None,
+    /// An arbitrary attach target, e.g., `kprobe/<function>`.
+    Any,
+    /// A tracepoint, i.e., `tp/<category>/<name>`.
+    Tracepoint,
+    /// A user space probe, i.e., `uprobe/<path>:<function>`.
+    Uprobe,
+    /// A user space statically defined tracepoint, i.e.,
+    /// `usdt/<path>:<provider>:<name>`.
+    Usdt,
+}
+
+impl Args {
+    /// Check whether `target` has the expected shape.
+    fn is_valid(&self, target: &str) -> bool {
+        let non_empty = |parts: &[&str]| parts.iter().all(|part| !part.is_empty());
+
+        match self {
+            Self::None => false,
+            Self::Any => true,
+            Self::Tracepoint => target
+                .split_once('/')
+                .is_some_and(|(category, name)| non_empty(&[category, name])),
+            Self::Uprobe => target
+                .split_once(':')
+                .is_some_and(|(path, function)| non_empty(&[path, function])),
+            Self::Usdt => {
+                let parts = target.splitn(3, ':').collect::<Vec<_>>();
+                parts.len() == 3 && non_empty(&parts)
+            },
+        }
+    }
+}
+
+
+/// The section definitions known to libbpf, along with the type of
+/// program they imply and the arguments they accept.
+///
+/// The list mirrors `section_defs` in libbpf's `libbpf.c`. Definitions
+/// taking arguments match the name itself as well as the name followed
+/// by `/` and the arguments. All others have to match exactly.
+const SECTION_DEFS: &[(&str, ProgramType, Args)] = &[
+    ("socket", ProgramType::SocketFilter, Args::None),
+    ("sk_reuseport/migrate", ProgramType::SkReuseport, Args::None),
+    ("sk_reuseport", ProgramType::SkReuseport, Args::None),
+    ("kprobe", ProgramType::Kprobe, Args::Any),
+    ("uprobe", ProgramType::Kprobe, Args::Uprobe),
+    ("uprobe.s", ProgramType::Kprobe, Args::Uprobe),
+    ("kretprobe", ProgramType::Kprobe, Args::Any),
+    ("uretprobe", ProgramType::Kprobe, Args::Uprobe),
+    ("uretprobe.s", ProgramType::Kprobe, Args::Uprobe),
+    ("kprobe.multi", ProgramType::Kprobe, Args::Any),
+    ("kretprobe.multi", ProgramType::Kprobe, Args::Any),
+    ("kprobe.session", ProgramType::Kprobe, Args::Any),
+    ("uprobe.multi", ProgramType::Kprobe, Args::Uprobe),
+    ("uretprobe.multi", ProgramType::Kprobe, Args::Uprobe),
+    ("uprobe.session", ProgramType::Kprobe, Args::Uprobe),
+    ("uprobe.multi.s", ProgramType::Kprobe, Args::Uprobe),
+    ("uretprobe.multi.s", ProgramType::Kprobe, Args::Uprobe),
+    ("uprobe.session.s", ProgramType::Kprobe, Args::Uprobe),
+    ("ksyscall", ProgramType::Kprobe, Args::Any),
+    ("kretsyscall", ProgramType::Kprobe, Args::Any),
+    ("usdt", ProgramType::Kprobe, Args::Usdt),
+    ("usdt.s", ProgramType::Kprobe, Args::Usdt),
+    ("tc/ingress", ProgramType::SchedCls, Args::None),
+    ("tc/egress", ProgramType::SchedCls, Args::None),
+    ("tcx/ingress", ProgramType::SchedCls, Args::None),
+    ("tcx/egress", ProgramType::SchedCls, Args::None),
+    ("tc", ProgramType::SchedCls, Args::None),
+    ("classifier", ProgramType::SchedCls, Args::None),
+    ("action", ProgramType::SchedAct, Args::None),
+    ("netkit/primary", ProgramType::SchedCls, Args::None),
+    ("netkit/peer", ProgramType::SchedCls, Args::None),
+    ("tracepoint", ProgramType::Tracepoint, Args::Tracepoint),
+    ("tp", ProgramType::Tracepoint, Args::Tracepoint),
+    ("raw_tracepoint", ProgramType::RawTracepoint, Args::Any),
+    ("raw_tp", ProgramType::RawTracepoint, Args::Any),
+    (
+        "raw_tracepoint.w",
+        ProgramType::RawTracepointWritable,
+        Args::Any,
+    ),
+    ("raw_tp.w", ProgramType::RawTracepointWritable, Args::Any),
+    ("tp_btf", ProgramType::Tracing, Args::Any),
+    ("fentry", ProgramType::Tracing, Args::Any),
+    ("fmod_ret", ProgramType::Tracing, Args::Any),
+    ("fexit", ProgramType::Tracing, Args::Any),
+    ("fentry.s", ProgramType::Tracing, Args::Any),
+    ("fmod_ret.s", ProgramType::Tracing, Args::Any),
+    ("fexit.s", ProgramType::Tracing, Args::Any),
+    ("freplace", ProgramType::Ext, Args::Any),
+    ("lsm", ProgramType::Lsm, Args::Any),
+    ("lsm.s", ProgramType::Lsm, Args::Any),
+    ("lsm_cgroup", ProgramType::Lsm, Args::Any),
+    ("iter", ProgramType::Tracing, Args::Any),
+    ("iter.s", ProgramType::Tracing, Args::Any),
+    ("syscall", ProgramType::Syscall, Args::None),
+    ("xdp.frags/devmap", ProgramType::Xdp, Args::None),
+    ("xdp/devmap", ProgramType::Xdp, Args::None),
+    ("xdp.frags/cpumap", ProgramType::Xdp, Args::None),
+    ("xdp/cpumap", ProgramType::Xdp, Args::None),
+    ("xdp.frags", ProgramType::Xdp, Args::None),
+    ("xdp", ProgramType::Xdp, Args::None),
+    ("perf_event", ProgramType::PerfEvent, Args::None),
+    ("lwt_in", ProgramType::LwtIn, Args::None),
+    ("lwt_out", ProgramType::LwtOut, Args::None),
+    ("lwt_xmit", ProgramType::LwtXmit, Args::None),
+    ("lwt_seg6local", ProgramType::LwtSeg6local, Args::None),
+    ("sockops", ProgramType::SockOps, Args::None),
+    ("sk_skb/stream_parser", ProgramType::SkSkb, Args::None),
+    ("sk_skb/stream_verdict", ProgramType::SkSkb, Args::None),
+    ("sk_skb/verdict", ProgramType::SkSkb, Args::None),
+    ("sk_skb", ProgramType::SkSkb, Args::None),
+    ("sk_msg", ProgramType::SkMsg, Args::None),
+    ("lirc_mode2", ProgramType::LircMode2, Args::None),
+    ("flow_dissector", ProgramType::FlowDissector, Args::None),
+    ("cgroup_skb/ingress", ProgramType::CgroupSkb, Args::None),
+    ("cgroup_skb/egress", ProgramType::CgroupSkb, Args::None),
+    ("cgroup/skb", ProgramType::CgroupSkb, Args::None),
+    ("cgroup/sock_create", ProgramType::CgroupSock, Args::None),
+    ("cgroup/sock_release", ProgramType::CgroupSock, Args::None),
+    ("cgroup/sock", ProgramType::CgroupSock, Args::None),
+    ("cgroup/post_bind4", ProgramType::CgroupSock, Args::None),
+    ("cgroup/post_bind6", ProgramType::CgroupSock, Args::None),
+    ("cgroup/bind4", ProgramType::CgroupSockAddr, Args::None),
+    ("cgroup/bind6", ProgramType::CgroupSockAddr, Args::None),
+    ("cgroup/connect4", ProgramType::CgroupSockAddr, Args::None),
+    ("cgroup/connect6", ProgramType::CgroupSockAddr, Args::None),
+    (
+        "cgroup/connect_unix",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    ("cgroup/sendmsg4", ProgramType::CgroupSockAddr, Args::None),
+    ("cgroup/sendmsg6", ProgramType::CgroupSockAddr, Args::None),
+    (
+        "cgroup/sendmsg_unix",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    ("cgroup/recvmsg4", ProgramType::CgroupSockAddr, Args::None),
+    ("cgroup/recvmsg6", ProgramType::CgroupSockAddr, Args::None),
+    (
+        "cgroup/recvmsg_unix",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getpeername4",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getpeername6",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getpeername_unix",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getsockname4",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getsockname6",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    (
+        "cgroup/getsockname_unix",
+        ProgramType::CgroupSockAddr,
+        Args::None,
+    ),
+    ("cgroup/sysctl", ProgramType::CgroupSysctl, Args::None),
+    ("cgroup/getsockopt", ProgramType::CgroupSockopt, Args::None),
+    ("cgroup/setsockopt", ProgramType::CgroupSockopt, Args::None),
+    ("cgroup/dev", ProgramType::CgroupDevice, Args::None),
+    ("struct_ops", ProgramType::StructOps, Args::Any),
+    ("struct_ops.s", ProgramType::StructOps, Args::Any),
+    ("sk_lookup", ProgramType::SkLookup, Args::None),
+    ("netfilter", ProgramType::Netfilter, Args::None),
+];
+
+/// Sections that libbpf handles specially, but that do not denote a
+/// program type: `.text` contains BPF subprograms and the remaining
+/// ones contain maps, global data, and meta data.
+const NON_PROGRAM_SECTIONS: &[&str] = &[
+    ".text",
+    ".maps",
+    "license",
+    "version",
+    ".kconfig",
+    ".ksyms",
+    ".data",
+    ".rodata",
+    ".bss",
+    ".struct_ops",
+    ".struct_ops.link",
+];
+
+/// Prefixes of sections containing custom global data, e.g.,
+/// `.data.foo`.
+const DATA_SECTION_PREFIXES: &[&str] = &[".data.", ".rodata.", ".bss."];
+
+
+/// A `SEC()` annotation of a BPF program, interpreted according to the
+/// section naming rules of libbpf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Section<'s> {
+    /// The kind of section, as defined by libbpf, e.g., `kprobe`,
+    /// `tp_btf`, or `tc/ingress`.
+    pub kind: &'static str,
+    /// The type of the program.
+    pub program_type: ProgramType,
+    /// The attach target, e.g., the function name `do_unlinkat` for a
+    /// section `kprobe/do_unlinkat`, if any.
+    pub target: Option<&'s str>,
+    /// Whether the program is sleepable.
+    pub sleepable: bool,
+}
+
+impl<'s> Section<'s> {
+    /// Parse the section name `section`, as provided to `SEC()`.
+    ///
+    /// `None` is returned if the section is not known to denote a BPF
+    /// program, as is the case for, say, `.maps` or `license`.
+    pub fn parse(section: &'s str) -> Option<Self> {
+        // A leading `?` merely disables auto-loading of the program.
+        let section = section.strip_prefix('?').unwrap_or(section);
+
+        SECTION_DEFS.iter().find_map(|&(kind, program_type, args)| {
+            let target = if args == Args::None {
+                if section != kind {
+                    return None
+                }
+                None
+            } else {
+                let rest = section.strip_prefix(kind)?;
+                if rest.is_empty() {
+                    None
+                } else {
+                    let target = rest.strip_prefix('/')?;
+                    (!target.is_empty()).then_some(target)
+                }
+            };
+
+            let slf = Self {
+                kind,
+                program_type,
+                target,
+                // libbpf marks sleepable variants with a `.s` suffix,
+                // while `syscall` programs are always sleepable.
+                sleepable: kind.ends_with(".s") || kind == "syscall",
+            };
+            Some(slf)
+        })
+    }
+}
+
+
+/// Compute the Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { diag } else { diag + 1 };
+            diag = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+
+/// Check that `section` is a section name known to libbpf with
+/// arguments of the expected shape.
+///
+/// If it is not, a corrected section name is suggested, if a plausible
+/// one could be found.
+pub(crate) fn validate(section: &str) -> Result<(), Option<String>> {
+    if NON_PROGRAM_SECTIONS.contains(&section)
+        || DATA_SECTION_PREFIXES
+            .iter()
+            .any(|prefix| section.starts_with(prefix))
+    {
+        return Ok(())
+    }
+
+    let (optional, section) = match section.strip_prefix('?') {
+        Some(section) => ("?", section),
+        None => ("", section),
+    };
+
+    if let Some(Section { kind, target, .. }) = Section::parse(section) {
+        // SANITY: The kind always originates from our definitions.
+        let (_kind, _program_type, args) =
+            SECTION_DEFS.iter().find(|(def, ..)| *def == kind).unwrap();
+        return match target {
+            Some(target) if !args.is_valid(target) => Err(None),
+            _ => Ok(()),
+        }
+    }
+
+    let (prefix, rest) = match section.split_once('/') {
+        Some((prefix, rest)) => (prefix, Some(rest)),
+        None => (section, None),
+    };
+    let suggestion = SECTION_DEFS
+        .iter()
+        .filter_map(|(kind, _program_type, args)| {
+            let (distance, suggestion) = match (args, rest) {
+                (Args::None, _) => (edit_distance(section, kind), kind.to_string()),
+                (_, Some(rest)) => (edit_distance(prefix, kind), format!("{kind}/{rest}")),
+                (_, None) => (edit_distance(prefix, kind), kind.to_string()),
+            };
+            let max_distance = (kind.len() / 3).max(1);
+            (distance <= max_distance && validate(&suggestion).is_ok())
+                .then_some((distance, suggestion))
+        })
+        .min_by_key(|(distance, _suggestion)| *distance)
+        .map(|(_distance, suggestion)| suggestion)
+        .or_else(|| {
+            // Sections that don't take arguments may have been provided
+            // some anyway.
+            SECTION_DEFS
+                .iter()
+                .any(|(kind, _program_type, args)| *args == Args::None && *kind == prefix)
+                .then(|| prefix.to_string())
+        });
+    Err(suggestion.map(|suggestion| format!("{optional}{suggestion}")))
+}
+
+
+/// Retrieve the section name of the function definition enclosing
+/// `node`, if any.
+pub(crate) fn enclosing_section<'code>(node: Node<'_>, code: &'code [u8]) -> Option<&'code str> {
+    let mut node = node;
+    while node.kind() != "function_definition" {
+        node = node.parent()?;
+    }
+
+    let mut cursor = node.walk();
+    let sec = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "sec_specifier")?;
+    let value = sec.child_by_field_name("value")?;
+    let value = str::from_utf8(&code[value.byte_range()]).ok()?;
+    value.strip_prefix('"')?.strip_suffix('"')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tree_sitter::Parser;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    /// Check that section names are mapped to the expected program
+    /// types.
+    #[test]
+    fn program_type_inference() {
+        let program_type = |section| Section::parse(section).map(|section| section.program_type);
+
+        assert_eq!(program_type("xdp"), Some(ProgramType::Xdp));
+        assert_eq!(program_type("xdp.frags/devmap"), Some(ProgramType::Xdp));
+        assert_eq!(program_type("kprobe"), Some(ProgramType::Kprobe));
+        assert_eq!(
+            program_type("kprobe/do_unlinkat"),
+            Some(ProgramType::Kprobe)
+        );
+        assert_eq!(
+            program_type("kprobe.multi/tcp_*"),
+            Some(ProgramType::Kprobe)
+        );
+        assert_eq!(
+            program_type("tp_btf/sched_switch"),
+            Some(ProgramType::Tracing)
+        );
+        assert_eq!(
+            program_type("raw_tp.w/bpf_test_finish"),
+            Some(ProgramType::RawTracepointWritable)
+        );
+        assert_eq!(program_type("lsm.s/file_open"), Some(ProgramType::Lsm));
+
+        // Sections without `+` have to match exactly.
+        assert_eq!(program_type("xdp/foo"), None);
+        assert_eq!(program_type("kprobes/foo"), None);
+        assert_eq!(program_type("license"), None);
+        assert_eq!(program_type(".maps"), None);
+    }
+
+    /// Check that sections are parsed into their components.
+    #[test]
+    fn section_parsing() {
+        assert_eq!(
+            Section::parse("kprobe/do_unlinkat"),
+            Some(Section {
+                kind: "kprobe",
+                program_type: ProgramType::Kprobe,
+                target: Some("do_unlinkat"),
+                sleepable: false,
+            })
+        );
+        assert_eq!(
+            Section::parse("uprobe.s//usr/lib/libc.so.6:malloc"),
+            Some(Section {
+                kind: "uprobe.s",
+                program_type: ProgramType::Kprobe,
+                target: Some("/usr/lib/libc.so.6:malloc"),
+                sleepable: true,
+            })
+        );
+        assert_eq!(
+            Section::parse("tc/ingress"),
+            Some(Section {
+                kind: "tc/ingress",
+                program_type: ProgramType::SchedCls,
+                target: None,
+                sleepable: false,
+            })
+        );
+        assert_eq!(
+            Section::parse("struct_ops.s/dequeue"),
+            Some(Section {
+                kind: "struct_ops.s",
+                program_type: ProgramType::StructOps,
+                target: Some("dequeue"),
+                sleepable: true,
+            })
+        );
+
+        let section = Section::parse("fentry/").unwrap();
+        assert_eq!(section.kind, "fentry");
+        assert_eq!(section.target, None);
+        assert!(Section::parse("syscall").unwrap().sleepable);
+        assert_eq!(
+            Section::parse("iter/task").unwrap().program_type,
+            ProgramType::Tracing
+        );
+    }
+
+    /// Check that our edit distance calculation works as expected.
+    #[test]
+    fn edit_distance_calculation() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kprobe", "kprobe"), 0);
+        assert_eq!(edit_distance("kprob", "kprobe"), 1);
+        assert_eq!(edit_distance("kprobe", "uprobe"), 1);
+        assert_eq!(edit_distance("tracepiont", "tracepoint"), 2);
+        assert_eq!(edit_distance("", "xdp"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    /// Check that section names are validated and that corrections are
+    /// suggested.
+    #[test]
+    fn section_validation() {
+        assert_eq!(validate("xdp"), Ok(()));
+        assert_eq!(validate("?xdp"), Ok(()));
+        assert_eq!(validate("kprobe"), Ok(()));
+        assert_eq!(validate("kprobe/do_unlinkat"), Ok(()));
+        assert_eq!(validate("tp/sched/sched_switch"), Ok(()));
+        assert_eq!(validate("uprobe//usr/lib/libc.so.6:malloc"), Ok(()));
+        assert_eq!(validate("usdt/libc.so.6:libc:setjmp"), Ok(()));
+        assert_eq!(validate("cgroup/connect4"), Ok(()));
+
+        // Sections not containing programs.
+        for section in NON_PROGRAM_SECTIONS {
+            assert_eq!(validate(section), Ok(()), "{section}");
+        }
+        assert_eq!(validate(".data.counters"), Ok(()));
+        assert_eq!(validate(".rodata.str1.1"), Ok(()));
+        assert_eq!(validate(".bss.foo"), Ok(()));
+
+        // Arguments of the wrong shape.
+        assert_eq!(validate("tracepoint/sched_switch"), Err(None));
+        assert_eq!(validate("uprobe/libc.so.6"), Err(None));
+        assert_eq!(validate("usdt/libc.so.6:setjmp"), Err(None));
+        // Arguments for sections not taking any.
+        assert_eq!(validate("xdp/foo"), Err(Some("xdp".to_string())));
+
+        // Typos.
+        assert_eq!(
+            validate("kprob/do_unlinkat"),
+            Err(Some("kprobe/do_unlinkat".to_string()))
+        );
+        assert_eq!(
+            validate("?kprob/do_unlinkat"),
+            Err(Some("?kprobe/do_unlinkat".to_string()))
+        );
+        assert_eq!(
+            validate("tracepiont/sched/sched_switch"),
+            Err(Some("tracepoint/sched/sched_switch".to_string()))
+        );
+        assert_eq!(
+            validate("cgroup/conect4"),
+            Err(Some("cgroup/connect4".to_string()))
+        );
+        assert_eq!(validate("xdp.frag"), Err(Some("xdp.frags".to_string())));
+        // A fixed kind with malformed arguments is no suggestion.
+        assert_eq!(validate("tracepiont/sched_switch"), Err(None));
+        assert_eq!(validate("foobar/baz"), Err(None));
+    }
+
+    /// Check that program type names can be converted back and forth.
+    #[test]
+    fn program_type_names() {
+        for (_def, program_type, _args) in SECTION_DEFS {
+            let name = program_type.to_string();
+            assert_eq!(name.parse::<ProgramType>().unwrap(), *program_type);
+        }
+
+        assert_eq!(
+            "sched_cls".parse::<ProgramType>().unwrap(),
+            ProgramType::SchedCls
+        );
+        let err = "tc".parse::<ProgramType>().unwrap_err();
+        assert_eq!(err.to_string(), "encountered unknown program type `tc`");
+    }
+
+    /// Check that we can find the section of the function enclosing a
+    /// node.
+    #[test]
+    fn enclosing_section_lookup() {
+        let code = br#"
+            SEC("xdp")
+            int xdp_prog(struct xdp_md *ctx) {
+                return foo();
+            }
+
+            static int bar(void) {
+                return foo();
+            }
+        "#;
+        let mut parser = Parser::new();
+        let () = parser.set_language(&LANGUAGE.into()).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let root = tree.root_node();
+
+        let offset = |needle: &str, nth: usize| {
+            let code = str::from_utf8(code).unwrap();
+            code.match_indices(needle).nth(nth).unwrap().0
+        };
+        let node = |offset| root.descendant_for_byte_range(offset, offset).unwrap();
+
+        assert_eq!(enclosing_section(node(offset("foo", 0)), code), Some("xdp"));
+        assert_eq!(enclosing_section(node(offset("foo", 1)), code), None);
+        assert_eq!(enclosing_section(root, code), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Replacement templates for creating fix suggestions from lint
+//! matches.
+
+use std::str;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::bail;
+
+use tree_sitter::Query;
+use tree_sitter::QueryCapture;
+
+
+/// A part of a replacement template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    /// Literal text, to be emitted as is.
+    Text(String),
+    /// A reference to a query capture, to be substituted with the
+    /// captured source code.
+    Capture {
+        /// The index of the referenced capture.
+        index: u32,
+        /// A suffix to strip from the captured source code, if present.
+        strip_suffix: Option<String>,
+    },
+}
+
+
+/// A template for the replacement of the code captured by a lint.
+///
+/// Templates are literal text that may reference query captures in the
+/// form of `{{capture}}`, which get substituted with the source code of
+/// the respective capture. `{{capture%suffix}}` additionally strips
+/// `suffix` from the captured source code, if present.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse a replacement template, resolving capture references
+    /// against those available in `query`.
+    pub fn parse(template: &str, query: &Query) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find("{{") {
+            let (text, reference) = rest.split_at(start);
+            if !text.is_empty() {
+                let () = segments.push(Segment::Text(text.to_string()));
+            }
+
+            let reference = &reference[2..];
+            let end = reference
+                .find("}}")
+                .with_context(|| format!("unterminated capture reference in `{template}`"))?;
+            let (name, strip_suffix) = match reference[..end].split_once('%') {
+                Some((name, suffix)) => (name, Some(suffix.to_string())),
+                None => (&reference[..end], None),
+            };
+            let Some(index) = query.capture_index_for_name(name) else {
+                bail!("template `{template}` references unknown capture `{name}`")
+            };
+            let () = segments.push(Segment::Capture {
+                index,
+                strip_suffix,
+            });
+            rest = &reference[end + 2..];
+        }
+
+        if !rest.is_empty() {
+            let () = segments.push(Segment::Text(rest.to_string()));
+        }
+
+        let slf = Self { segments };
+        Ok(slf)
+    }
+
+    /// Expand the template using the provided query captures of a
+    /// match on `code`.
+    ///
+    /// `None` is returned if a referenced capture is not part of the
+    /// match or does not contain valid UTF-8.
+    pub fn expand(&self, captures: &[QueryCapture<'_>], code: &[u8]) -> Option<String> {
+        let mut expanded = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => expanded.push_str(text),
+                Segment::Capture {
+                    index,
+                    strip_suffix,
+                } => {
+                    let capture = captures.iter().find(|capture| capture.index == *index)?;
+                    let text = str::from_utf8(&code[capture.node.byte_range()]).ok()?;
+                    let text = strip_suffix
+                        .as_deref()
+                        .and_then(|suffix| text.strip_suffix(suffix))
+                        .unwrap_or(text);
+                    let () = expanded.push_str(text);
+                },
+            }
+        }
+        Some(expanded)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tree_sitter::Parser;
+    use tree_sitter::QueryCursor;
+    use tree_sitter::StreamingIterator as _;
+    use tree_sitter_bpf_c::LANGUAGE;
+
+
+    fn query() -> Query {
+        let code = r#"
+            (call_expression
+                function: (identifier) @function
+                arguments: (argument_list (identifier) @__arg)
+            )
+        "#;
+        Query::new(&LANGUAGE.into(), code).unwrap()
+    }
+
+    /// Check that we can parse replacement templates.
+    #[test]
+    fn template_parsing() {
+        let query = query();
+        let template = Template::parse("{{function}}_btf({{__arg%_size}}) {}", &query).unwrap();
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Capture {
+                    index: 0,
+                    strip_suffix: None,
+                },
+                Segment::Text("_btf(".to_string()),
+                Segment::Capture {
+                    index: 1,
+                    strip_suffix: Some("_size".to_string()),
+                },
+                Segment::Text(") {}".to_string()),
+            ]
+        );
+
+        let template = Template::parse("foo", &query).unwrap();
+        assert_eq!(template.segments, vec![Segment::Text("foo".to_string())]);
+
+        let err = Template::parse("{{bar}}", &query).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "template `{{bar}}` references unknown capture `bar`"
+        );
+
+        let err = Template::parse("{{function", &query).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unterminated capture reference in `{{function`"
+        );
+    }
+
+    /// Check that templates are expanded as expected.
+    #[test]
+    fn template_expansion() {
+        let query = query();
+        let code = b"foo(key_size);";
+        let mut parser = Parser::new();
+        let () = parser.set_language(&LANGUAGE.into()).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), code.as_slice());
+        let m = matches.next().unwrap();
+
+        let template = Template::parse("{{function}}({{__arg%_size}})", &query).unwrap();
+        assert_eq!(
+            template.expand(m.captures, code).as_deref(),
+            Some("foo(key)")
+        );
+
+        let template = Template::parse("{{__arg%_type}}", &query).unwrap();
+        assert_eq!(
+            template.expand(m.captures, code).as_deref(),
+            Some("key_size")
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Tests for the `gpl-only-helper` lint.
+
+use indoc::indoc;
+
+use pretty_assertions::assert_eq;
+
+use crate::util::lint_report;
+
+
+/// Check that GPL-only helpers are flagged when the license is not GPL
+/// compatible.
+#[test]
+fn incompatible_license() {
+    let code = indoc! { r#"
+        SEC("tp_btf/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            struct task_struct *prev = (struct task_struct *)ctx[1];
+            int pid;
+            bpf_probe_read_kernel(&pid, sizeof(pid), &prev->pid);
+            return bpf_get_smp_processor_id();
+        }
+        char LICENSE[] SEC("license") = "MIT";
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [gpl-only-helper] helper or kfunc may only be called by programs with a GPL-compatible license, but the declared license is not
+          --> <stdin>:5:4
+          | 
+        5 |     bpf_probe_read_kernel(&pid, sizeof(pid), &prev->pid);
+          |     ^^^^^^^^^^^^^^^^^^^^^
+          | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+
+    let code = code.replace(
+        r#"char LICENSE[] SEC("license")"#,
+        r#"char _license[] __attribute__((section("license"), used))"#,
+    );
+    assert_eq!(lint_report(code), expected);
+}
+
+
+/// Make sure that GPL-only helpers are accepted with a GPL compatible
+/// license, or when the license is missing or cannot be determined.
+#[test]
+fn compatible_license() {
+    let program = indoc! { r#"
+        SEC("tp_btf/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            struct task_struct *prev = (struct task_struct *)ctx[1];
+            int pid;
+            bpf_probe_read_kernel(&pid, sizeof(pid), &prev->pid);
+            return 0;
+        }
+    "# };
+
+    for license in [
+        "",
+        r#"char LICENSE[] SEC("license") = "Dual BSD/GPL";"#,
+        r#"char LICENSE[] SEC("license") = "GPL";"#,
+        r#"char LICENSE[] SEC("license") = LICENSE_STR;"#,
+        r#"char _license[] __attribute__((section("license"), used)) = "GPL";"#,
+    ] {
+        let code = format!("{program}{license}\n");
+        assert_eq!(lint_report(&code), "", "{license}");
+    }
+}
@@ -0,0 +1,126 @@
+//! Tests for the `invalid-section` lint.
+
+use indoc::indoc;
+
+use pretty_assertions::assert_eq;
+
+use crate::util::lint_report;
+
+
+/// Check that typos in section names are flagged, along with a
+/// suggested correction.
+#[test]
+fn misspelled_section() {
+    let code = indoc! { r#"
+        SEC("tp_btff/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            return 0;
+        }
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [invalid-section] section name is not known to libbpf or its arguments are malformed; the program will likely fail to load or attach
+          --> <stdin>:0:4
+          | 
+        0 | SEC("tp_btff/sched_switch")
+          |     ^^^^^^^^^^^^^^^^^^^^^^
+          | 
+        help: apply the suggested fix
+          | 
+        0 - SEC("tp_btff/sched_switch")
+        0 + SEC("tp_btf/sched_switch")
+          | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+/// Check that sections with malformed arguments are flagged.
+#[test]
+fn malformed_arguments() {
+    let code = indoc! { r#"
+        SEC("tracepoint/sched_switch")
+        int handle__sched_switch(void *ctx)
+        {
+            return 0;
+        }
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [invalid-section] section name is not known to libbpf or its arguments are malformed; the program will likely fail to load or attach
+          --> <stdin>:0:4
+          | 
+        0 | SEC("tracepoint/sched_switch")
+          |     ^^^^^^^^^^^^^^^^^^^^^^^^^
+          | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+/// Make sure that valid sections as well as those of non-functions are
+/// not flagged.
+#[test]
+fn valid_sections() {
+    let code = indoc! { r#"
+        char LICENSE[] SEC("license") = "GPL";
+
+        struct {
+            __uint(type, BPF_MAP_TYPE_RINGBUF);
+            __uint(max_entries, 256 * 1024);
+        } events SEC(".maps");
+
+        SEC("tp/sched/sched_switch")
+        int handle__sched_switch(void *ctx)
+        {
+            return 0;
+        }
+
+        SEC("?xdp.frags")
+        int handle_xdp(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+
+        SEC("uprobe//usr/lib/libc.so.6:malloc")
+        int BPF_UPROBE(handle_malloc, size_t size)
+        {
+            return 0;
+        }
+    "# };
+    assert_eq!(lint_report(code), "");
+}
+
+
+/// Make sure that sections not containing BPF programs are not flagged,
+/// even when annotating functions.
+#[test]
+fn non_program_sections() {
+    for section in [
+        ".text",
+        ".maps",
+        "license",
+        "version",
+        ".kconfig",
+        ".ksyms",
+        ".data",
+        ".data.counters",
+        ".rodata",
+        ".rodata.str1.1",
+        ".bss",
+        ".bss.foo",
+        ".struct_ops",
+        ".struct_ops.link",
+    ] {
+        let code = format!(
+            indoc! { r#"
+                SEC("{}")
+                int helper(int x)
+                {{
+                    return x + 1;
+                }}
+            "# },
+            section
+        );
+        assert_eq!(lint_report(&code), "", "{section}");
+    }
+}
@@ -0,0 +1,118 @@
+//! Tests for the `missing-license` lint.
+
+use std::collections::HashMap;
+
+use indoc::indoc;
+
+use pretty_assertions::assert_eq;
+
+use bpflint::Level;
+use bpflint::LintOpts;
+
+use crate::util::lint_report;
+use crate::util::lint_report_opts;
+
+
+/// Lint `code` with the `missing-license` lint, which is not checked
+/// by default, enabled.
+fn lint_report_license(code: &str) -> String {
+    let opts = LintOpts {
+        levels: HashMap::from([("missing-license".to_string(), Level::Warn)]),
+        ..Default::default()
+    };
+    lint_report_opts(code, &opts)
+}
+
+
+/// Check that a missing license is reported once, at the first program.
+#[test]
+fn missing() {
+    let code = indoc! { r#"
+        SEC("xdp")
+        int xdp_prog1(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+
+        SEC("xdp")
+        int xdp_prog2(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [missing-license] file defines BPF programs but does not declare a license, e.g., `char LICENSE[] SEC("license") = "GPL";`, which prevents use of GPL-only helpers and kfuncs
+          --> <stdin>:0:4
+          | 
+        0 | SEC("xdp")
+          |     ^^^^^
+          | 
+    "# };
+    assert_eq!(lint_report_license(code), expected);
+}
+
+
+/// Make sure that no issue is reported when a license is declared.
+#[test]
+fn declared() {
+    let code = indoc! { r#"
+        char LICENSE[] SEC("license") = "Dual BSD/GPL";
+
+        SEC("xdp")
+        int xdp_prog(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+    "# };
+    assert_eq!(lint_report_license(code), "");
+
+    let code = indoc! { r#"
+        SEC("xdp")
+        int xdp_prog(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+
+        char _license[] SEC("license") = LICENSE;
+    "# };
+    assert_eq!(lint_report_license(code), "");
+
+    let code = indoc! { r#"
+        char _license[] __attribute__((section("license"), used)) = "GPL";
+
+        SEC("xdp")
+        int xdp_prog(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+    "# };
+    assert_eq!(lint_report_license(code), "");
+}
+
+
+/// Check that files not defining any programs are not flagged.
+#[test]
+fn no_programs() {
+    let code = indoc! { r#"
+        static __always_inline int helper(int x)
+        {
+            return x + 1;
+        }
+    "# };
+    assert_eq!(lint_report_license(code), "");
+}
+
+
+/// Check that the lint is not checked unless enabled explicitly.
+#[test]
+fn disabled_by_default() {
+    let code = indoc! { r#"
+        SEC("xdp")
+        int xdp_prog(struct xdp_md *ctx)
+        {
+            return XDP_PASS;
+        }
+    "# };
+    assert_eq!(lint_report(code), "");
+}
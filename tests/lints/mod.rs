@@ -11,10 +11,20 @@ mod bpf_loop;
 mod core_read;
 #[path = "get-current-task.rs"]
 mod get_current_task;
+#[path = "gpl-only-helper.rs"]
+mod gpl_only_helper;
+#[path = "invalid-section.rs"]
+mod invalid_section;
+#[path = "missing-license.rs"]
+mod missing_license;
 #[path = "perfbuf-usage.rs"]
 mod perfbuf_usage;
 #[path = "probe-read.rs"]
 mod probe_read;
+#[path = "ringbuf-leak.rs"]
+mod ringbuf_leak;
+#[path = "unavailable-helper.rs"]
+mod unavailable_helper;
 #[path = "unrolled-for-loop.rs"]
 mod unrolled_for_loop;
 #[path = "unstable-attach-point.rs"]
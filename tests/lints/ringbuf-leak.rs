@@ -0,0 +1,79 @@
+//! Tests for the `ringbuf-leak` lint.
+
+use indoc::indoc;
+
+use pretty_assertions::assert_eq;
+
+use crate::util::lint_report;
+
+
+/// Check that exits reached with an outstanding reservation are
+/// flagged.
+#[test]
+fn leaked_reservation() {
+    let code = indoc! { r#"
+        SEC("tp_btf/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            struct event *e = bpf_ringbuf_reserve(&events, sizeof(*e), 0);
+            if (!e)
+                return 0;
+            if (bpf_get_current_pid_tgid() >> 32 == 0)
+                return 0;
+            bpf_ringbuf_submit(e, 0);
+            return 0;
+        }
+
+        static void emit(void)
+        {
+            struct event *e = bpf_ringbuf_reserve(&events, sizeof(*e), 0);
+            if (e)
+                e->pid = 1;
+        }
+        char LICENSE[] SEC("license") = "GPL";
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [ringbuf-leak] ring buffer reservation may not be submitted or discarded on a path reaching this point; the verifier will reject the program with an "unreleased reference" error
+          --> <stdin>:7:8
+          | 
+        7 |         return 0;
+          |         ^^^^^^^^^
+          | 
+        warning: [ringbuf-leak] ring buffer reservation may not be submitted or discarded on a path reaching this point; the verifier will reject the program with an "unreleased reference" error
+          --> <stdin>:17:0
+           | 
+        17 | }
+           | ^
+           | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+
+/// Make sure that reservations released on all paths are not flagged.
+#[test]
+fn released_reservation() {
+    let code = indoc! { r#"
+        SEC("tp_btf/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            struct event *e;
+
+            e = bpf_ringbuf_reserve(&events, sizeof(*e), 0);
+            if (e == NULL)
+                return 0;
+            if (bpf_get_current_pid_tgid() >> 32 == 0)
+                goto discard;
+
+            bpf_ringbuf_submit(e, 0);
+            return 0;
+
+        discard:
+            bpf_ringbuf_discard(e, 0);
+            return 0;
+        }
+        char LICENSE[] SEC("license") = "GPL";
+    "# };
+    assert_eq!(lint_report(code), "");
+}
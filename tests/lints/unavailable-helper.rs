@@ -0,0 +1,123 @@
+//! Tests for the `unavailable-helper` lint.
+
+use bpflint::LintOpts;
+use bpflint::Version;
+
+use indoc::indoc;
+
+use pretty_assertions::assert_eq;
+
+use crate::util::lint_report;
+use crate::util::lint_report_opts;
+
+
+/// Check that helpers introduced after the targeted kernel version are
+/// flagged.
+#[test]
+fn kernel_version_too_old() {
+    let code = indoc! { r#"
+        SEC("tp_btf/sched_switch")
+        int handle__sched_switch(u64 *ctx)
+        {
+            struct event *e = bpf_ringbuf_reserve(&events, sizeof(*e), 0);
+            if (!e)
+                return 0;
+            bpf_ringbuf_submit(e, 0);
+            return 0;
+        }
+    "# };
+
+    let lint_opts = LintOpts {
+        kernel_version: Some(Version(5, 4, 0)),
+        ..Default::default()
+    };
+    let expected = indoc! { r#"
+        warning: [unavailable-helper] helper or kfunc is not available for the targeted kernel version or for the type of the program it is called from
+          --> <stdin>:3:22
+          | 
+        3 |     struct event *e = bpf_ringbuf_reserve(&events, sizeof(*e), 0);
+          |                       ^^^^^^^^^^^^^^^^^^^
+          | 
+        warning: [unavailable-helper] helper or kfunc is not available for the targeted kernel version or for the type of the program it is called from
+          --> <stdin>:6:4
+          | 
+        6 |     bpf_ringbuf_submit(e, 0);
+          |     ^^^^^^^^^^^^^^^^^^
+          | 
+    "# };
+    assert_eq!(lint_report_opts(code, &lint_opts), expected);
+
+    let lint_opts = LintOpts {
+        kernel_version: Some(Version(5, 8, 0)),
+        ..Default::default()
+    };
+    assert_eq!(lint_report_opts(code, &lint_opts), "");
+    // Without a targeted kernel version there is nothing to flag.
+    assert_eq!(lint_report(code), "");
+}
+
+/// Check that helpers not callable from the program type implied by the
+/// enclosing function's section are flagged.
+#[test]
+fn program_type_mismatch() {
+    let code = indoc! { r#"
+        SEC("tc/ingress")
+        int handle_ingress(struct __sk_buff *skb)
+        {
+            bpf_xdp_adjust_head(skb, 4);
+            bpf_skb_pull_data(skb, 0);
+            return TC_ACT_OK;
+        }
+
+        SEC("xdp")
+        int handle_xdp(struct xdp_md *ctx)
+        {
+            bpf_xdp_adjust_head(ctx, 4);
+            bpf_skb_pull_data(ctx, 0);
+            return XDP_PASS;
+        }
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [unavailable-helper] helper or kfunc is not available for the targeted kernel version or for the type of the program it is called from
+          --> <stdin>:3:4
+          | 
+        3 |     bpf_xdp_adjust_head(skb, 4);
+          |     ^^^^^^^^^^^^^^^^^^^
+          | 
+        warning: [unavailable-helper] helper or kfunc is not available for the targeted kernel version or for the type of the program it is called from
+          --> <stdin>:12:4
+           | 
+        12 |     bpf_skb_pull_data(ctx, 0);
+           |     ^^^^^^^^^^^^^^^^^
+           | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+/// Make sure that we do not flag helpers where the program type can't
+/// be inferred or when they aren't known to us.
+#[test]
+fn unknown_program_type() {
+    let code = indoc! { r#"
+        static int helper(struct __sk_buff *skb)
+        {
+            return bpf_xdp_adjust_head(skb, 4);
+        }
+
+        SEC("freplace/handle_xdp")
+        int replacement(struct xdp_md *ctx)
+        {
+            bpf_skb_pull_data(ctx, 0);
+            return XDP_PASS;
+        }
+
+        SEC("xdp")
+        int handle_xdp(struct xdp_md *ctx)
+        {
+            bpf_unknown_helper(ctx);
+            return XDP_PASS;
+        }
+    "# };
+    assert_eq!(lint_report(code), "");
+}
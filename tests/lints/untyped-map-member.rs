@@ -23,6 +23,59 @@ fn basic_sizeof() {
         2 |     __uint(key_size, sizeof(b));
           |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^
           | 
+        help: apply the suggested fix
+          | 
+        2 -     __uint(key_size, sizeof(b));
+        2 +     __type(key, b);
+          | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+
+/// Check that we suggest the proper replacement for type names.
+#[test]
+fn sizeof_type() {
+    let code = indoc! { r#"
+        struct {
+            __uint(value_size, sizeof(struct event));
+        } name;
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [untyped-map-member] __uint(<a>_size, sizeof(<b>)) does not contain potentially relevant type information, consider using __type(<a>, <b>) instead
+          --> <stdin>:1:4
+          | 
+        1 |     __uint(value_size, sizeof(struct event));
+          |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+          | 
+        help: apply the suggested fix
+          | 
+        1 -     __uint(value_size, sizeof(struct event));
+        1 +     __type(value, struct event);
+          | 
+    "# };
+    assert_eq!(lint_report(code), expected);
+}
+
+
+/// Make sure that we still flag `sizeof` of arbitrary expressions, but
+/// don't suggest a fix for them.
+#[test]
+fn sizeof_expression() {
+    let code = indoc! { r#"
+        struct {
+            __uint(value_size, sizeof(e.comm));
+        } name;
+    "# };
+
+    let expected = indoc! { r#"
+        warning: [untyped-map-member] __uint(<a>_size, sizeof(<b>)) does not contain potentially relevant type information, consider using __type(<a>, <b>) instead
+          --> <stdin>:1:4
+          | 
+        1 |     __uint(value_size, sizeof(e.comm));
+          |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+          | 
     "# };
     assert_eq!(lint_report(code), expected);
 }
@@ -2,18 +2,29 @@
 
 use std::path::Path;
 
-use bpflint::lint;
+use bpflint::LintOpts;
+use bpflint::builtin_lints;
+use bpflint::lint_custom_opts;
 use bpflint::terminal::report;
 
 
 /// Lint `code` and report matches as a string created using
 /// [`terminal::report`].
 pub fn lint_report<C>(code: C) -> String
+where
+    C: AsRef<[u8]>,
+{
+    lint_report_opts(code, &LintOpts::default())
+}
+
+/// Lint `code` using the provided options and report matches as a
+/// string created using [`terminal::report`].
+pub fn lint_report_opts<C>(code: C, lint_opts: &LintOpts) -> String
 where
     C: AsRef<[u8]>,
 {
     let mut r = Vec::new();
-    let () = lint(code.as_ref())
+    let () = lint_custom_opts(code.as_ref(), builtin_lints(), lint_opts)
         .unwrap()
         .into_iter()
         .try_for_each(|m| report(&m, code.as_ref(), Path::new("<stdin>"), &mut r))